@@ -1,30 +1,192 @@
 /* tslint:disable */
 /* eslint-disable */
-/**
-*/
+
+export interface GameOptions {
+    /** how many columns wide the board is, from 4 to 16. Defaults to 10 */
+    cols?: number;
+    /** how many rows the player can see, from 4 to 50. Defaults to 20 */
+    rows?: number;
+    /** how many rows sit above the visible ones for pieces to spawn into, from 2 to 20.
+     * Defaults to 4 */
+    hidden_rows?: number;
+    rule_set?: "guideline" | "nes" | "tgm";
+    /** marathon goes until topping out, while sprint is a race to clear sprint_lines lines.
+     * Defaults to marathon */
+    mode?: "marathon" | "sprint";
+    /** how many lines a sprint takes. Defaults to 40 */
+    sprint_lines?: number;
+    /** how big each cell is drawn, in pixels. Defaults to 25 */
+    block_size?: number;
+    /** the first game's seed. Two games with the same seed and rules get the same pieces */
+    seed?: number;
+    /** how the keys feel. These are only the page's defaults: anything the player's changed with
+     * set_handling takes priority */
+    handling?: HandlingOptions;
+    /** keeps this game's saved progress, key bindings and handling separate from other games on the page */
+    id?: string;
+    /** listen for keys anywhere on the page rather than just when the canvas has focus, so that
+     * games with different bindings can be played side by side on one keyboard */
+    global_keys?: boolean;
+}
+
+
+
+export interface HandlingOptions {
+    /** how long left or right has to be held before it starts repeating, in ms. Defaults to 167 */
+    das_ms?: number;
+    /** how long between repeats once it has, in ms. 0 slides straight to the wall. Defaults
+     * to 33 */
+    arr_ms?: number;
+    /** how many times faster than gravity a held soft drop falls. Defaults to 20 */
+    soft_drop_factor?: number;
+    /** how long auto shift holds off after a rotation, in ms. Defaults to 0 */
+    das_cut_ms?: number;
+}
+
+
+
+export type PieceKind = "I" | "O" | "T" | "S" | "Z" | "J" | "L";
+
+export type GameEvent =
+| { type: "piece_spawned"; piece: PieceKind }
+| { type: "piece_locked"; piece: PieceKind }
+/** sent as soon as the rows fill, before they're cleared away */
+| { type: "lines_cleared"; count: number; kind: "Single" | "Double" | "Triple" | "Tetris" }
+/** T-spins, plus spins with other pieces under rule sets that count them */
+| { type: "spin"; piece: PieceKind; kind: "Mini" | "Full" }
+| { type: "combo"; combo: number }
+| { type: "level_up"; level: number }
+/** the piece that's just gone into hold */
+| { type: "hold"; piece: PieceKind }
+| { type: "game_over"; score: number; lines: number; ticks: number }
+| { type: "paused" }
+| { type: "resumed" };
+
+export type GameEventName = GameEvent["type"];
+
+
+
+export class Game {
+    free(): void;
+    [Symbol.dispose](): void;
+    bind_key(action: string, code: string): void;
+    bindings(): string;
+    constructor(target: HTMLCanvasElement | string, options?: GameOptions | null);
+    destroy(): void;
+    download_replay(): void;
+    handling(): string;
+    high_scores(): string;
+    load_replay(bytes: Uint8Array): void;
+    load_replay_text(text: string): void;
+    off(event: GameEventName, callback: (event: GameEvent) => void): void;
+    on(event: GameEventName, callback: (event: GameEvent) => void): void;
+    pause(): void;
+    player_name(): string;
+    replay_text(): string;
+    reset(): void;
+    reset_bindings(): void;
+    reset_handling(): void;
+    resume(): void;
+    set_handling(handling: HandlingOptions): void;
+    set_player_name(name: string): void;
+    set_replay_speed(speed: number): void;
+    start(): void;
+    step_replay(): void;
+    stop_replay(): void;
+    toggle_replay_pause(): void;
+    unbind_key(code: string): void;
+    watch_high_score(table: string, rank: number): void;
+    watch_replay(): void;
+    readonly did_win: boolean;
+    readonly held_piece: string | undefined;
+    readonly is_game_over: boolean;
+    readonly is_paused: boolean;
+    readonly is_running: boolean;
+    readonly level: number;
+    readonly lines: number;
+    readonly queue: string[];
+    readonly score: number;
+    readonly ticks: number;
+}
+
 export function start(): void;
 
 export type InitInput = RequestInfo | URL | Response | BufferSource | WebAssembly.Module;
 
 export interface InitOutput {
-  readonly memory: WebAssembly.Memory;
-  readonly start: () => void;
-  readonly __wbindgen_malloc: (a: number) => number;
-  readonly __wbindgen_realloc: (a: number, b: number, c: number) => number;
-  readonly __wbindgen_export_2: WebAssembly.Table;
-  readonly _dyn_core__ops__function__FnMut__A____Output___R_as_wasm_bindgen__closure__WasmClosure___describe__invoke__ha6266361fc884972: (a: number, b: number, c: number) => void;
-  readonly _dyn_core__ops__function__FnMut_____Output___R_as_wasm_bindgen__closure__WasmClosure___describe__invoke__hc1b52d340060ec82: (a: number, b: number) => void;
-  readonly __wbindgen_exn_store: (a: number) => void;
-  readonly __wbindgen_start: () => void;
+    readonly memory: WebAssembly.Memory;
+    readonly __wbg_game_free: (a: number, b: number) => void;
+    readonly game_bind_key: (a: number, b: number, c: number, d: number, e: number) => [number, number];
+    readonly game_bindings: (a: number) => [number, number];
+    readonly game_create: (a: any, b: number) => [number, number, number];
+    readonly game_destroy: (a: number) => void;
+    readonly game_did_win: (a: number) => number;
+    readonly game_download_replay: (a: number) => [number, number];
+    readonly game_handling: (a: number) => [number, number];
+    readonly game_held_piece: (a: number) => [number, number];
+    readonly game_high_scores: (a: number) => [number, number];
+    readonly game_is_game_over: (a: number) => number;
+    readonly game_is_paused: (a: number) => number;
+    readonly game_is_running: (a: number) => number;
+    readonly game_level: (a: number) => number;
+    readonly game_lines: (a: number) => number;
+    readonly game_load_replay: (a: number, b: number, c: number) => [number, number];
+    readonly game_load_replay_text: (a: number, b: number, c: number) => [number, number];
+    readonly game_off: (a: number, b: any, c: any) => [number, number];
+    readonly game_on: (a: number, b: any, c: any) => [number, number];
+    readonly game_pause: (a: number) => void;
+    readonly game_player_name: (a: number) => [number, number];
+    readonly game_queue: (a: number) => [number, number];
+    readonly game_replay_text: (a: number) => [number, number];
+    readonly game_reset: (a: number) => void;
+    readonly game_reset_bindings: (a: number) => [number, number];
+    readonly game_reset_handling: (a: number) => [number, number];
+    readonly game_resume: (a: number) => void;
+    readonly game_score: (a: number) => number;
+    readonly game_set_handling: (a: number, b: any) => [number, number];
+    readonly game_set_player_name: (a: number, b: number, c: number) => [number, number];
+    readonly game_set_replay_speed: (a: number, b: number) => void;
+    readonly game_start: (a: number) => [number, number];
+    readonly game_step_replay: (a: number) => void;
+    readonly game_stop_replay: (a: number) => void;
+    readonly game_ticks: (a: number) => number;
+    readonly game_toggle_replay_pause: (a: number) => void;
+    readonly game_unbind_key: (a: number, b: number, c: number) => [number, number];
+    readonly game_watch_high_score: (a: number, b: number, c: number, d: number) => [number, number];
+    readonly game_watch_replay: (a: number) => void;
+    readonly start: () => void;
+    readonly wasm_bindgen__convert__closures_____invoke__h2cc996b5ec6ec1db: (a: number, b: number, c: number) => void;
+    readonly wasm_bindgen__convert__closures_____invoke__hf2f514391ff4f395: (a: number, b: number, c: any) => void;
+    readonly __wbindgen_malloc: (a: number, b: number) => number;
+    readonly __wbindgen_realloc: (a: number, b: number, c: number, d: number) => number;
+    readonly __wbindgen_exn_store: (a: number) => void;
+    readonly __externref_table_alloc: () => number;
+    readonly __wbindgen_externrefs: WebAssembly.Table;
+    readonly __wbindgen_destroy_closure: (a: number, b: number) => void;
+    readonly __externref_table_dealloc: (a: number) => void;
+    readonly __wbindgen_free: (a: number, b: number, c: number) => void;
+    readonly __externref_drop_slice: (a: number, b: number) => void;
+    readonly __wbindgen_start: () => void;
 }
 
+export type SyncInitInput = BufferSource | WebAssembly.Module;
+
+/**
+ * Instantiates the given `module`, which can either be bytes or
+ * a precompiled `WebAssembly.Module`.
+ *
+ * @param {{ module: SyncInitInput }} module - Passing `SyncInitInput` directly is deprecated.
+ *
+ * @returns {InitOutput}
+ */
+export function initSync(module: { module: SyncInitInput } | SyncInitInput): InitOutput;
+
 /**
-* If `module_or_path` is {RequestInfo} or {URL}, makes a request and
-* for everything else, calls `WebAssembly.instantiate` directly.
-*
-* @param {InitInput | Promise<InitInput>} module_or_path
-*
-* @returns {Promise<InitOutput>}
-*/
-export default function init (module_or_path?: InitInput | Promise<InitInput>): Promise<InitOutput>;
-        
\ No newline at end of file
+ * If `module_or_path` is {RequestInfo} or {URL}, makes a request and
+ * for everything else, calls `WebAssembly.instantiate` directly.
+ *
+ * @param {{ module_or_path: InitInput | Promise<InitInput> }} module_or_path - Passing `InitInput` directly is deprecated.
+ *
+ * @returns {Promise<InitOutput>}
+ */
+export default function __wbg_init (module_or_path?: { module_or_path: InitInput | Promise<InitInput> } | InitInput | Promise<InitInput>): Promise<InitOutput>;
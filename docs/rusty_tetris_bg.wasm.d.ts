@@ -1,11 +1,55 @@
 /* tslint:disable */
 /* eslint-disable */
 export const memory: WebAssembly.Memory;
-export function start(): void;
-export function __wbindgen_malloc(a: number): number;
-export function __wbindgen_realloc(a: number, b: number, c: number): number;
-export const __wbindgen_export_2: WebAssembly.Table;
-export function _dyn_core__ops__function__FnMut__A____Output___R_as_wasm_bindgen__closure__WasmClosure___describe__invoke__ha6266361fc884972(a: number, b: number, c: number): void;
-export function _dyn_core__ops__function__FnMut_____Output___R_as_wasm_bindgen__closure__WasmClosure___describe__invoke__hc1b52d340060ec82(a: number, b: number): void;
-export function __wbindgen_exn_store(a: number): void;
-export function __wbindgen_start(): void;
+export const __wbg_game_free: (a: number, b: number) => void;
+export const game_bind_key: (a: number, b: number, c: number, d: number, e: number) => [number, number];
+export const game_bindings: (a: number) => [number, number];
+export const game_create: (a: any, b: number) => [number, number, number];
+export const game_destroy: (a: number) => void;
+export const game_did_win: (a: number) => number;
+export const game_download_replay: (a: number) => [number, number];
+export const game_handling: (a: number) => [number, number];
+export const game_held_piece: (a: number) => [number, number];
+export const game_high_scores: (a: number) => [number, number];
+export const game_is_game_over: (a: number) => number;
+export const game_is_paused: (a: number) => number;
+export const game_is_running: (a: number) => number;
+export const game_level: (a: number) => number;
+export const game_lines: (a: number) => number;
+export const game_load_replay: (a: number, b: number, c: number) => [number, number];
+export const game_load_replay_text: (a: number, b: number, c: number) => [number, number];
+export const game_off: (a: number, b: any, c: any) => [number, number];
+export const game_on: (a: number, b: any, c: any) => [number, number];
+export const game_pause: (a: number) => void;
+export const game_player_name: (a: number) => [number, number];
+export const game_queue: (a: number) => [number, number];
+export const game_replay_text: (a: number) => [number, number];
+export const game_reset: (a: number) => void;
+export const game_reset_bindings: (a: number) => [number, number];
+export const game_reset_handling: (a: number) => [number, number];
+export const game_resume: (a: number) => void;
+export const game_score: (a: number) => number;
+export const game_set_handling: (a: number, b: any) => [number, number];
+export const game_set_player_name: (a: number, b: number, c: number) => [number, number];
+export const game_set_replay_speed: (a: number, b: number) => void;
+export const game_start: (a: number) => [number, number];
+export const game_step_replay: (a: number) => void;
+export const game_stop_replay: (a: number) => void;
+export const game_ticks: (a: number) => number;
+export const game_toggle_replay_pause: (a: number) => void;
+export const game_unbind_key: (a: number, b: number, c: number) => [number, number];
+export const game_watch_high_score: (a: number, b: number, c: number, d: number) => [number, number];
+export const game_watch_replay: (a: number) => void;
+export const start: () => void;
+export const wasm_bindgen__convert__closures_____invoke__h2cc996b5ec6ec1db: (a: number, b: number, c: number) => void;
+export const wasm_bindgen__convert__closures_____invoke__hf2f514391ff4f395: (a: number, b: number, c: any) => void;
+export const __wbindgen_malloc: (a: number, b: number) => number;
+export const __wbindgen_realloc: (a: number, b: number, c: number, d: number) => number;
+export const __wbindgen_exn_store: (a: number) => void;
+export const __externref_table_alloc: () => number;
+export const __wbindgen_externrefs: WebAssembly.Table;
+export const __wbindgen_destroy_closure: (a: number, b: number) => void;
+export const __externref_table_dealloc: (a: number) => void;
+export const __wbindgen_free: (a: number, b: number, c: number) => void;
+export const __externref_drop_slice: (a: number, b: number) => void;
+export const __wbindgen_start: () => void;
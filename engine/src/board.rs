@@ -0,0 +1,303 @@
+use crate::piece::PieceKind;
+
+// each row is a u16 bitmask, so this is as wide as it gets
+pub const MAX_COLS: i32 = 16;
+// any narrower or shorter and not every piece can turn around
+const MIN_COLS: i32 = 4;
+const MIN_ROWS: i32 = 4;
+// pieces spawn in the bottom two of these
+const MIN_HIDDEN_ROWS: i32 = 2;
+// far taller than anyone would play on, but small enough that the board and canvas stay cheap
+// and replays have room to write them down
+pub const MAX_ROWS: i32 = 50;
+pub const MAX_HIDDEN_ROWS: i32 = 20;
+
+// how big the playing field is, in cells
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dimensions {
+	pub cols: i32,
+	// just the rows the player can see
+	pub visible_rows: i32,
+	// buffer above the visible rows where pieces spawn
+	pub hidden_rows: i32,
+}
+
+impl Default for Dimensions {
+	// the standard 10x20
+	fn default() -> Dimensions {
+		Dimensions {
+			cols: 10,
+			visible_rows: 20,
+			hidden_rows: 4,
+		}
+	}
+}
+
+impl Dimensions {
+	pub fn new(cols: i32, visible_rows: i32, hidden_rows: i32) -> Dimensions {
+		Dimensions {
+			cols,
+			visible_rows,
+			hidden_rows,
+		}
+	}
+
+	pub fn total_rows(self) -> i32 {
+		self.visible_rows + self.hidden_rows
+	}
+
+	// the nearest thing the engine can actually play on
+	pub fn clamped(self) -> Dimensions {
+		Dimensions {
+			cols: self.cols.clamp(MIN_COLS, MAX_COLS),
+			visible_rows: self.visible_rows.clamp(MIN_ROWS, MAX_ROWS),
+			hidden_rows: self.hidden_rows.clamp(MIN_HIDDEN_ROWS, MAX_HIDDEN_ROWS),
+		}
+	}
+}
+
+// Covers the hidden rows too, so y = 0 is the top of the buffer above what the player can see.
+// One bitmask per row (bit x set means column x is filled) for quick collision and line checks,
+// plus which kind of piece left each square behind so we know what to draw.
+#[derive(Debug, Clone)]
+pub struct Board {
+	dimensions: Dimensions,
+	full_row: u16,
+	rows: Vec<u16>,
+	cells: Vec<Option<PieceKind>>,
+}
+
+// only the cells are saved, since the row masks follow from them
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SavedBoard {
+	dimensions: Dimensions,
+	cells: Vec<Option<PieceKind>>,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Board {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		SavedBoard {
+			dimensions: self.dimensions,
+			cells: self.cells.clone(),
+		}
+		.serialize(serializer)
+	}
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Board {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Board, D::Error> {
+		let saved = SavedBoard::deserialize(deserializer)?;
+		let mut board = Board::new(saved.dimensions);
+		if saved.cells.len() != board.cells.len() || board.dimensions != saved.dimensions {
+			return Err(serde::de::Error::invalid_length(
+				saved.cells.len(),
+				&"a cell for every square on the board",
+			));
+		}
+
+		let width = board.width() as usize;
+		for (index, cell) in saved.cells.into_iter().enumerate() {
+			if let Some(kind) = cell {
+				board.set((index % width) as i32, (index / width) as i32, kind);
+			}
+		}
+		Ok(board)
+	}
+}
+
+impl Default for Board {
+	fn default() -> Board {
+		Board::new(Dimensions::default())
+	}
+}
+
+impl Board {
+	pub fn new(dimensions: Dimensions) -> Board {
+		let dimensions = dimensions.clamped();
+		let height = dimensions.total_rows() as usize;
+		Board {
+			dimensions,
+			full_row: ((1u32 << dimensions.cols) - 1) as u16,
+			rows: vec![0; height],
+			cells: vec![None; dimensions.cols as usize * height],
+		}
+	}
+
+	pub fn clear(&mut self) {
+		self.rows.iter_mut().for_each(|row| *row = 0);
+		self.cells.iter_mut().for_each(|cell| *cell = None);
+	}
+
+	pub fn dimensions(&self) -> Dimensions {
+		self.dimensions
+	}
+
+	pub fn width(&self) -> i32 {
+		self.dimensions.cols
+	}
+
+	// all of it, hidden rows included
+	pub fn height(&self) -> i32 {
+		self.dimensions.total_rows()
+	}
+
+	pub fn hidden_rows(&self) -> i32 {
+		self.dimensions.hidden_rows
+	}
+
+	pub fn visible_rows(&self) -> i32 {
+		self.dimensions.visible_rows
+	}
+
+	// anything past the walls or floor counts as occupied, while anything above the board is open
+	pub fn is_occupied(&self, x: i32, y: i32) -> bool {
+		if !(0..self.width()).contains(&x) || y >= self.height() {
+			return true;
+		}
+		if y < 0 {
+			return false;
+		}
+		self.rows[y as usize] & (1 << x) != 0
+	}
+
+	pub fn get(&self, x: i32, y: i32) -> Option<PieceKind> {
+		self.index(x, y).and_then(|index| self.cells[index])
+	}
+
+	pub fn row(&self, y: i32) -> u16 {
+		self.rows[y as usize]
+	}
+
+	pub fn is_row_full(&self, y: i32) -> bool {
+		self.rows[y as usize] == self.full_row
+	}
+
+	pub fn has_full_rows(&self) -> bool {
+		self.rows.contains(&self.full_row)
+	}
+
+	pub fn full_row_count(&self) -> u32 {
+		self.rows
+			.iter()
+			.filter(|row| **row == self.full_row)
+			.count() as u32
+	}
+
+	// whether clearing the full rows would leave nothing behind
+	pub fn is_perfect_clear(&self) -> bool {
+		self.has_full_rows()
+			&& self
+				.rows
+				.iter()
+				.all(|row| *row == 0 || *row == self.full_row)
+	}
+
+	pub(crate) fn set(&mut self, x: i32, y: i32, kind: PieceKind) {
+		if let Some(index) = self.index(x, y) {
+			self.rows[y as usize] |= 1 << x;
+			self.cells[index] = Some(kind);
+		}
+	}
+
+	// drops every full row, shifting whatever was above it down. Returns how many were removed
+	pub(crate) fn remove_full_rows(&mut self) -> u32 {
+		let mut removed = 0;
+		// going top to bottom means a removal only ever shifts rows we've already checked
+		for y in 0..self.rows.len() {
+			if self.rows[y] == self.full_row {
+				self.remove_row(y);
+				removed += 1;
+			}
+		}
+		removed
+	}
+
+	fn index(&self, x: i32, y: i32) -> Option<usize> {
+		if !(0..self.width()).contains(&x) || !(0..self.height()).contains(&y) {
+			return None;
+		}
+		Some(y as usize * self.width() as usize + x as usize)
+	}
+
+	fn remove_row(&mut self, y: usize) {
+		let width = self.width() as usize;
+		self.rows.copy_within(0..y, 1);
+		self.rows[0] = 0;
+
+		self.cells.copy_within(0..y * width, width);
+		for cell in self.cells[..width].iter_mut() {
+			*cell = None;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn fill_row(board: &mut Board, y: i32) {
+		for x in 0..board.width() {
+			board.set(x, y, PieceKind::I);
+		}
+	}
+
+	#[test]
+	fn removing_full_rows_drops_everything_above() {
+		let mut board = Board::default();
+		let bottom = board.height() - 1;
+		fill_row(&mut board, bottom);
+		board.set(3, bottom - 1, PieceKind::T);
+		fill_row(&mut board, bottom - 2);
+		board.set(0, bottom - 3, PieceKind::S);
+
+		assert!(board.is_row_full(bottom));
+		assert!(!board.is_row_full(bottom - 1));
+		assert_eq!(board.remove_full_rows(), 2);
+
+		assert!(!board.has_full_rows());
+		assert_eq!(board.get(3, bottom), Some(PieceKind::T));
+		assert_eq!(board.row(bottom), 1 << 3);
+		assert_eq!(board.get(0, bottom - 1), Some(PieceKind::S));
+		assert_eq!(board.row(bottom - 1), 1);
+		for y in 0..bottom - 1 {
+			assert_eq!(board.row(y), 0);
+			assert!((0..board.width()).all(|x| board.get(x, y).is_none()));
+		}
+	}
+
+	#[test]
+	fn perfect_clears_leave_nothing_behind() {
+		let mut board = Board::new(Dimensions::new(6, 10, 2));
+		fill_row(&mut board, 11);
+		fill_row(&mut board, 10);
+		assert!(board.is_perfect_clear());
+		board.set(0, 9, PieceKind::O);
+		assert!(!board.is_perfect_clear());
+	}
+
+	#[test]
+	fn dimensions_stay_within_limits() {
+		assert_eq!(
+			Dimensions::new(1_000, 1_000_000_000, 1_000).clamped(),
+			Dimensions::new(MAX_COLS, MAX_ROWS, MAX_HIDDEN_ROWS)
+		);
+		assert_eq!(
+			Dimensions::new(0, -5, 0).clamped(),
+			Dimensions::new(MIN_COLS, MIN_ROWS, MIN_HIDDEN_ROWS)
+		);
+	}
+
+	#[test]
+	fn walls_and_floor_are_solid_but_the_sky_is_open() {
+		let board = Board::new(Dimensions::new(4, 4, 2));
+		assert!(board.is_occupied(-1, 0));
+		assert!(board.is_occupied(4, 0));
+		assert!(board.is_occupied(0, 6));
+		assert!(!board.is_occupied(0, -1));
+		assert!(!board.is_occupied(3, 5));
+	}
+}
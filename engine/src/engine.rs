@@ -0,0 +1,1128 @@
+use crate::board::Board;
+use crate::event::{Event, Observer};
+use crate::gravity::{FRAMES_PER_SECOND, ONE_G};
+use crate::handling::{AutoShift, Handling};
+use crate::input::{Button, Input};
+use crate::piece::{Piece, PieceKind};
+use crate::randomizer::{PieceSource, Randomizer};
+use crate::replay::Replay;
+use crate::rules::{LockReset, Rules, MAX_PREVIEW_LEN};
+use crate::scoring::{LineClear, Scoring};
+use crate::spin;
+use crate::srs::{self, Kick, RotationDirection};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+// the simulation runs at a fixed rate, however often the frontend gets to draw
+pub const TICKS_PER_SECOND: u32 = 60;
+
+// bump whenever a saved Engine from before wouldn't load into the one now, so the frontend can
+// tell an old save apart from a broken one
+pub const SAVE_VERSION: u32 = 1;
+
+// how long full rows stick around before they're cleared
+const LINE_CLEAR_DELAY: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameSummary {
+	pub score: u32,
+	pub lines: u32,
+	pub ticks: u64,
+}
+
+impl GameSummary {
+	pub fn duration(&self) -> Duration {
+		duration_of(self.ticks)
+	}
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Engine {
+	is_game_over: bool,
+	did_win: bool,
+	scoring: Scoring,
+	ticks: u64,
+	summary: Option<GameSummary>,
+
+	current_piece: Option<Piece>,
+	held_piece: Option<PieceKind>,
+	// only one hold per piece, until it locks
+	can_hold: bool,
+	queue: VecDeque<PieceKind>,
+	board: Board,
+
+	ticks_to_wait: u32,
+
+	// how long the current piece has spent on the ground, and how many times moving it has
+	// bought it more time. Getting lower than it's ever been wipes both
+	lock_ticks: u32,
+	lock_resets: u32,
+	lowest_row: i32,
+
+	// how far the current piece has fallen towards the next cell, as a fraction of ONE_G
+	// for each tick per second
+	gravity_progress: u64,
+
+	should_send_to_bottom: bool,
+	should_hold: bool,
+
+	rotations_to_perform: i32,
+	should_rotate_half: bool,
+	last_kick: Option<Kick>,
+	// spins only count if rotating was the last thing the piece did
+	did_rotate_last: bool,
+	last_clear: Option<(LineClear, u64)>,
+	x_to_move: i32,
+	auto_shift: AutoShift,
+	is_soft_dropping: bool,
+	// what the player was doing last tick, so we can tell when they let go of something
+	last_input: Input,
+
+	rules: Rules,
+	seed: u64,
+	pieces: PieceSource,
+	// everything that's happened so far this game. None if the pieces come from a randomizer
+	// we can't rebuild, since the replay would be no use without them
+	replay: Option<Replay>,
+	// whoever's listening for events. They're not part of the game itself, so they don't get saved
+	#[cfg_attr(feature = "serde", serde(skip))]
+	observers: Vec<Box<dyn Observer>>,
+}
+
+impl Engine {
+	pub fn new(rules: Rules, seed: u64) -> Engine {
+		let pieces = PieceSource::new(rules.randomizer, seed);
+		Engine::with_pieces(rules, seed, pieces)
+	}
+
+	// plays with pieces from anywhere, rather than rules.randomizer. Games like this can't be
+	// replayed or saved, since there's no telling what the randomizer would do a second time
+	pub fn with_randomizer(rules: Rules, randomizer: Box<dyn Randomizer>) -> Engine {
+		Engine::with_pieces(rules, 0, PieceSource::custom(randomizer))
+	}
+
+	fn with_pieces(rules: Rules, seed: u64, pieces: PieceSource) -> Engine {
+		let mut engine = Engine {
+			is_game_over: false,
+			did_win: false,
+			scoring: Scoring::new(rules.lines_per_level),
+			ticks: 0,
+			summary: None,
+
+			current_piece: None,
+			held_piece: None,
+			can_hold: true,
+			queue: VecDeque::with_capacity(MAX_PREVIEW_LEN),
+			board: Board::new(rules.dimensions),
+
+			ticks_to_wait: 0,
+
+			lock_ticks: 0,
+			lock_resets: 0,
+			lowest_row: 0,
+
+			gravity_progress: 0,
+
+			should_send_to_bottom: false,
+			should_hold: false,
+
+			rotations_to_perform: 0,
+			should_rotate_half: false,
+			last_kick: None,
+			did_rotate_last: false,
+			last_clear: None,
+			x_to_move: 0,
+			auto_shift: AutoShift::default(),
+			is_soft_dropping: false,
+			last_input: Input::default(),
+
+			replay: if pieces.is_rebuildable() {
+				Some(Replay::new(rules.clone(), seed))
+			} else {
+				None
+			},
+			pieces,
+			rules,
+			seed,
+			observers: Vec::new(),
+		};
+		engine.fill_queue();
+		engine
+	}
+
+	// starts a fresh game with the same rules. A randomizer from outside has no seed to take, so
+	// it just carries on where it left off
+	pub fn reset(&mut self, seed: u64) {
+		if self.pieces.is_rebuildable() {
+			self.seed = seed;
+			self.pieces = PieceSource::new(self.rules.randomizer, seed);
+			self.replay = Some(Replay::new(self.rules.clone(), seed));
+		}
+		self.is_game_over = false;
+		self.did_win = false;
+		self.scoring = Scoring::new(self.rules.lines_per_level);
+		self.ticks = 0;
+		self.gravity_progress = 0;
+		self.summary = None;
+		self.last_clear = None;
+		self.current_piece = None;
+		self.held_piece = None;
+		self.can_hold = true;
+		self.should_hold = false;
+		self.should_send_to_bottom = false;
+		self.rotations_to_perform = 0;
+		self.should_rotate_half = false;
+		self.x_to_move = 0;
+		self.release_all();
+		self.queue.clear();
+		self.fill_queue();
+		self.board.clear();
+		self.ticks_to_wait = 0;
+	}
+
+	// hears about everything from here on, across resets, for as long as the engine's around
+	pub fn observe(&mut self, observer: impl Observer + 'static) {
+		self.observers.push(Box::new(observer));
+	}
+
+	// pausing is up to whoever's ticking the engine, so this just lets the observers know
+	pub fn notify_paused(&mut self, is_paused: bool) {
+		self.emit(if is_paused {
+			Event::Paused
+		} else {
+			Event::Resumed
+		});
+	}
+
+	// handling's recorded along with the rest of the rules, so it can only change before the game
+	// gets under way. Returns whether it took
+	pub fn set_handling(&mut self, handling: Handling) -> bool {
+		if self.ticks > 0 {
+			return false;
+		}
+		self.rules.handling = handling;
+		if let Some(replay) = &mut self.replay {
+			*replay = Replay::new(self.rules.clone(), self.seed);
+		}
+		true
+	}
+
+	pub fn rules(&self) -> &Rules {
+		&self.rules
+	}
+
+	pub fn seed(&self) -> u64 {
+		self.seed
+	}
+
+	// the game so far, ready to be played back. None for games using Engine::with_randomizer
+	pub fn replay(&self) -> Option<&Replay> {
+		self.replay.as_ref()
+	}
+
+	pub fn is_game_over(&self) -> bool {
+		self.is_game_over
+	}
+
+	pub fn did_win(&self) -> bool {
+		self.did_win
+	}
+
+	pub fn score(&self) -> u32 {
+		self.scoring.score()
+	}
+
+	pub fn scoring(&self) -> &Scoring {
+		&self.scoring
+	}
+
+	pub fn lines(&self) -> u32 {
+		self.scoring.lines()
+	}
+
+	pub fn level(&self) -> u32 {
+		self.scoring.level()
+	}
+
+	// in cells per frame, see gravity::ONE_G
+	pub fn gravity(&self) -> u32 {
+		self.rules.gravity.gravity(self.scoring.level())
+	}
+
+	// how long this game has been going, in ticks
+	pub fn ticks(&self) -> u64 {
+		self.ticks
+	}
+
+	// how long this game has been going, going by the ticks rather than the wall clock, so a
+	// replay of it takes exactly as long
+	pub fn elapsed(&self) -> Duration {
+		duration_of(self.ticks)
+	}
+
+	// only available once the game is over
+	pub fn summary(&self) -> Option<&GameSummary> {
+		self.summary.as_ref()
+	}
+
+	pub fn current_piece(&self) -> Option<&Piece> {
+		self.current_piece.as_ref()
+	}
+
+	pub fn held_piece(&self) -> Option<PieceKind> {
+		self.held_piece
+	}
+
+	// false once the current piece has been swapped with the held one
+	pub fn can_hold(&self) -> bool {
+		self.can_hold
+	}
+
+	// the upcoming pieces, soonest first
+	pub fn queue(&self) -> &VecDeque<PieceKind> {
+		&self.queue
+	}
+
+	// which kick let the current piece's most recent rotation through, if it's been rotated
+	pub fn last_kick(&self) -> Option<Kick> {
+		self.last_kick
+	}
+
+	// everything that's been sealed in so far. Any full rows are about to be cleared
+	pub fn board(&self) -> &Board {
+		&self.board
+	}
+
+	// the most recent lock that scored something, along with the tick it happened on
+	pub fn last_clear(&self) -> Option<(LineClear, u64)> {
+		self.last_clear
+	}
+
+	// how close the current piece is to locking, from 0 to 1
+	pub fn lock_progress(&self) -> f64 {
+		self.lock_ticks as f64 / self.lock_delay_ticks() as f64
+	}
+
+	// how far the current piece would fall if sent to the bottom, for drawing its ghost
+	pub fn ghost_offset(&self) -> Option<i32> {
+		self.current_piece
+			.as_ref()
+			.map(|piece| Engine::get_interception_point(piece, &self.board))
+	}
+
+	// runs one tick of the game with whatever the player's doing. Should be called
+	// TICKS_PER_SECOND times a second while the game isn't paused
+	pub fn tick(&mut self, input: Input) {
+		if self.is_game_over {
+			return;
+		}
+		self.ticks += 1;
+		if let Some(replay) = &mut self.replay {
+			replay.record(input);
+		}
+
+		self.apply_input(input);
+
+		// DAS keeps charging between pieces, but there's nothing to repeat onto until the next spawns
+		let shift = self.auto_shift.tick(&self.rules.handling);
+		if self.current_piece.is_some() {
+			let width = self.board.width();
+			self.x_to_move = (self.x_to_move + shift).clamp(-width, width);
+		}
+
+		self.update();
+		self.fall();
+	}
+
+	fn apply_input(&mut self, input: Input) {
+		let last_input = std::mem::replace(&mut self.last_input, input);
+
+		// presses go first, so that a tap that's already been let go still does something
+		for button in Button::ALL.iter().copied() {
+			if input.was_pressed(button) {
+				self.press(button);
+			}
+		}
+		for button in Button::ALL.iter().copied() {
+			let was_down = last_input.is_held(button) || input.was_pressed(button);
+			if was_down && !input.is_held(button) {
+				self.release(button);
+			}
+		}
+	}
+
+	// NOTE: these only queue up a move; it's applied during the next update
+	fn press(&mut self, button: Button) {
+		match button {
+			// moves one cell right away, then auto shifts for as long as it's held
+			Button::Left => {
+				self.auto_shift.press(-1);
+				self.x_to_move = -1;
+			}
+			Button::Right => {
+				self.auto_shift.press(1);
+				self.x_to_move = 1;
+			}
+			// soft drop speeds up gravity for as long as it's held
+			Button::SoftDrop => self.is_soft_dropping = true,
+			Button::HardDrop => self.should_send_to_bottom = true,
+			Button::RotateClockwise => self.rotations_to_perform += 1,
+			Button::RotateCounterClockwise => self.rotations_to_perform -= 1,
+			Button::Rotate180 => self.should_rotate_half = true,
+			Button::Hold => self.should_hold = true,
+		}
+	}
+
+	fn release(&mut self, button: Button) {
+		match button {
+			Button::Left => self.auto_shift.release(-1),
+			Button::Right => self.auto_shift.release(1),
+			Button::SoftDrop => self.is_soft_dropping = false,
+			_ => {}
+		}
+	}
+
+	// lets go of everything
+	fn release_all(&mut self) {
+		self.last_input = Input::default();
+		self.auto_shift = AutoShift::default();
+		self.is_soft_dropping = false;
+	}
+
+	// lets gravity do its thing and counts down to locking
+	fn fall(&mut self) {
+		if self.ticks_to_wait > 0 {
+			self.ticks_to_wait -= 1;
+			return;
+		}
+
+		let mut gravity = self.rules.gravity.gravity(self.scoring.level()) as u64;
+		if self.is_soft_dropping {
+			gravity = gravity.saturating_mul(self.rules.handling.clamped().soft_drop_factor as u64);
+		}
+		// falling the whole board in a frame is as fast as it gets, so anything past that is no
+		// different
+		gravity = gravity.min(self.board.height() as u64 * ONE_G as u64);
+
+		let mut cells_fallen = 0;
+		if let Some(current_piece) = &mut self.current_piece {
+			// gravity is per 60th of a second, so scale it to however often we're ticked
+			self.gravity_progress = self
+				.gravity_progress
+				.saturating_add(gravity.saturating_mul(FRAMES_PER_SECOND as u64));
+			let per_cell = ONE_G as u64 * TICKS_PER_SECOND as u64;
+			while self.gravity_progress >= per_cell {
+				self.gravity_progress -= per_cell;
+				current_piece.top_left.y += 1;
+				if Engine::does_collide(current_piece, &self.board) {
+					current_piece.top_left.y -= 1;
+					// resting on something, so there's nothing to build up towards
+					self.gravity_progress = 0;
+					break;
+				}
+				cells_fallen += 1;
+				self.did_rotate_last = false;
+			}
+		}
+		if cells_fallen > 0 {
+			if self.is_soft_dropping {
+				self.scoring.soft_drop(cells_fallen);
+			}
+			self.on_piece_moved();
+		}
+
+		// the lock timer only runs while we're resting on something
+		if self.is_grounded() {
+			self.lock_ticks += 1;
+			if self.lock_ticks >= self.lock_delay_ticks() {
+				self.lock_piece();
+			}
+		}
+	}
+
+	// applies whatever moves were queued up, and brings in the next piece once the last one's gone
+	fn update(&mut self) {
+		if self.ticks_to_wait > 0 {
+			return;
+		}
+
+		if self.current_piece.is_none() {
+			// fix the grid
+			self.board.remove_full_rows();
+
+			let kind = self.next_piece();
+			self.can_hold = true;
+			if !self.spawn(kind) {
+				return;
+			}
+		}
+
+		// after the spawn, so that holding while the last piece locks swaps out the next one
+		if self.should_hold {
+			self.should_hold = false;
+			if self.can_hold {
+				if let Some(current_piece) = self.current_piece.take() {
+					self.can_hold = false;
+					self.emit(Event::Hold {
+						piece: current_piece.kind,
+					});
+					let kind = match self.held_piece.replace(current_piece.kind) {
+						Some(kind) => kind,
+						None => self.next_piece(),
+					};
+					if !self.spawn(kind) {
+						return;
+					}
+				}
+			}
+		}
+		let mut did_move = false;
+		let mut should_lock = false;
+		if let Some(current_piece) = &mut self.current_piece {
+			// move left/right
+			{
+				let x_delta = if self.x_to_move > 0 { 1 } else { -1 };
+				while self.x_to_move != 0 {
+					self.x_to_move -= x_delta;
+					current_piece.top_left.x += x_delta;
+					if Engine::does_collide(current_piece, &self.board) {
+						current_piece.top_left.x -= x_delta;
+						break;
+					} else {
+						did_move = true;
+						self.did_rotate_last = false;
+					}
+				}
+			}
+
+			// rotate
+			{
+				let quarter_turn = if self.rotations_to_perform > 0 {
+					RotationDirection::Clockwise
+				} else {
+					RotationDirection::CounterClockwise
+				};
+				let quarter_turns = self.rotations_to_perform.unsigned_abs() as usize;
+				let half_turn = if self.should_rotate_half {
+					Some(RotationDirection::Half)
+				} else {
+					None
+				};
+				self.rotations_to_perform = 0;
+				self.should_rotate_half = false;
+
+				let directions = std::iter::repeat_n(quarter_turn, quarter_turns).chain(half_turn);
+				for direction in directions {
+					if let Some(kick) = srs::rotate(current_piece, direction, &self.board) {
+						self.last_kick = Some(kick);
+						self.did_rotate_last = true;
+						self.auto_shift.cut(&self.rules.handling);
+						did_move = true;
+					}
+				}
+			}
+
+			// hard drop. This goes last so that anything pressed alongside it still happens before
+			// we lock
+			if self.should_send_to_bottom {
+				self.should_send_to_bottom = false;
+				let mut cells_moved = 0;
+				loop {
+					current_piece.top_left.y += 1;
+					if Engine::does_collide(current_piece, &self.board) {
+						// undo last move
+						current_piece.top_left.y -= 1;
+						break;
+					}
+					cells_moved += 1;
+					self.did_rotate_last = false;
+				}
+				self.scoring.hard_drop(cells_moved);
+				should_lock = true;
+			}
+		}
+
+		if should_lock {
+			self.lock_piece();
+		} else if did_move {
+			self.on_piece_moved();
+		}
+	}
+
+	// seals the current piece into the board
+	fn lock_piece(&mut self) {
+		let current_piece = match self.current_piece.take() {
+			Some(piece) => piece,
+			None => return,
+		};
+
+		// has to be checked before the piece becomes part of the board
+		let spin = if self.did_rotate_last {
+			spin::detect(
+				&current_piece,
+				&self.board,
+				self.last_kick,
+				self.rules.all_spin,
+			)
+		} else {
+			None
+		};
+
+		// add to board
+		for position in current_piece.cells() {
+			self.board.set(position.x, position.y, current_piece.kind);
+		}
+
+		// lock out: the whole piece sealed above the visible rows
+		let is_locked_out = current_piece
+			.cells()
+			.all(|position| position.y < self.board.hidden_rows());
+
+		self.emit(Event::PieceLocked {
+			piece: current_piece.kind,
+		});
+
+		let level = self.scoring.level();
+		let cleared = self.board.full_row_count();
+		if let Some(clear) = self
+			.scoring
+			.lock(cleared, spin, self.board.is_perfect_clear())
+		{
+			self.last_clear = Some((clear, self.ticks));
+			if let Some(kind) = clear.kind {
+				self.emit(Event::LinesCleared {
+					count: cleared,
+					kind,
+				});
+			}
+			if let Some(spin) = clear.spin {
+				self.emit(Event::Spin(spin));
+			}
+			if clear.combo > 0 {
+				self.emit(Event::Combo { combo: clear.combo });
+			}
+		}
+		if self.scoring.level() > level {
+			self.emit(Event::LevelUp {
+				level: self.scoring.level(),
+			});
+		}
+
+		// the winning lock counts even if it tops out
+		if let Some(goal) = self.rules.mode.line_goal() {
+			if self.scoring.lines() >= goal {
+				self.did_win = true;
+				self.end_game();
+				return;
+			}
+		}
+
+		if is_locked_out {
+			self.end_game();
+			return;
+		}
+
+		// full rows stick around for a bit so they can be drawn before being cleared
+		if self.board.has_full_rows() {
+			self.ticks_to_wait = ticks_for(LINE_CLEAR_DELAY);
+		}
+	}
+
+	// works out whether a successful move should buy the piece more time before it locks
+	fn on_piece_moved(&mut self) {
+		let y = match &self.current_piece {
+			Some(piece) => piece.top_left.y,
+			None => return,
+		};
+
+		let lock_delay = self.rules.lock_delay.clamped();
+		if y > self.lowest_row {
+			// reaching new depths always gets a fresh timer, along with a fresh set of resets
+			self.lowest_row = y;
+			self.lock_resets = 0;
+			self.lock_ticks = 0;
+		} else if lock_delay.reset == LockReset::Move
+			&& self.lock_ticks > 0
+			&& self.lock_resets < lock_delay.max_resets
+		{
+			self.lock_resets += 1;
+			self.lock_ticks = 0;
+		}
+	}
+
+	fn is_grounded(&self) -> bool {
+		match &self.current_piece {
+			Some(current_piece) => {
+				let mut below = current_piece.clone();
+				below.top_left.y += 1;
+				Engine::does_collide(&below, &self.board)
+			}
+			None => false,
+		}
+	}
+
+	fn lock_delay_ticks(&self) -> u32 {
+		ticks_for(self.rules.lock_delay.clamped().delay)
+	}
+
+	// puts a fresh piece at the top of the board, in its spawn orientation. Returns false if
+	// that ended the game
+	fn spawn(&mut self, kind: PieceKind) -> bool {
+		let piece = Piece::spawn(kind, self.board.dimensions());
+		self.last_kick = None;
+		self.did_rotate_last = false;
+		self.gravity_progress = 0;
+		self.lock_ticks = 0;
+		self.lock_resets = 0;
+		self.lowest_row = piece.top_left.y;
+
+		// block out: there's no room for the new piece
+		if Engine::does_collide(&piece, &self.board) {
+			self.end_game();
+			return false;
+		}
+		self.current_piece = Some(piece);
+		self.emit(Event::PieceSpawned { piece: kind });
+		true
+	}
+
+	fn preview_len(&self) -> usize {
+		self.rules.preview_len.clamp(1, MAX_PREVIEW_LEN)
+	}
+
+	fn fill_queue(&mut self) {
+		while self.queue.len() < self.preview_len() {
+			self.queue.push_back(self.pieces.next());
+		}
+	}
+
+	fn next_piece(&mut self) -> PieceKind {
+		self.fill_queue();
+		let next = self.queue.pop_front().unwrap();
+		self.fill_queue();
+		next
+	}
+
+	fn end_game(&mut self) {
+		let summary = GameSummary {
+			score: self.scoring.score(),
+			lines: self.scoring.lines(),
+			ticks: self.ticks,
+		};
+		self.is_game_over = true;
+		self.summary = Some(summary);
+		self.emit(Event::GameOver(summary));
+	}
+
+	fn emit(&mut self, event: Event) {
+		for observer in self.observers.iter_mut() {
+			observer.notify(&event);
+		}
+	}
+
+	fn get_interception_point(current_piece: &Piece, board: &Board) -> i32 {
+		let mut extra_y = 0;
+		let mut temp_piece = current_piece.clone(); // clone to get mutable version
+		loop {
+			temp_piece.top_left.y += 1;
+			extra_y += 1;
+			if Engine::does_collide(&temp_piece, board) {
+				return extra_y - 1;
+			}
+		}
+	}
+
+	fn does_collide(current_piece: &Piece, board: &Board) -> bool {
+		current_piece
+			.cells()
+			.any(|position| board.is_occupied(position.x, position.y))
+	}
+}
+
+// how long that many ticks take, to the nanosecond
+fn duration_of(ticks: u64) -> Duration {
+	Duration::from_nanos((ticks as u128 * 1_000_000_000 / TICKS_PER_SECOND as u128) as u64)
+}
+
+// how many ticks it takes to wait out a duration, to the nearest tick but always at least one
+pub(crate) fn ticks_for(duration: Duration) -> u32 {
+	let ticks = (duration.as_millis() * TICKS_PER_SECOND as u128 + 500) / 1000;
+	(ticks as u32).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::board::Dimensions;
+	use crate::gravity::GravityTable;
+	use crate::replay::Playback;
+	use crate::rules::{GameMode, LockDelay};
+	use crate::scoring::ClearKind;
+	use std::cell::RefCell;
+	use std::rc::Rc;
+
+	fn pressing(button: Button) -> Input {
+		let mut input = Input::default();
+		input.press(button);
+		input
+	}
+
+	// fills every row from top down, leaving the left column open so nothing ever clears
+	fn stack_from(engine: &mut Engine, top: i32) {
+		for y in top..engine.board.height() {
+			for x in 1..engine.board.width() {
+				engine.board.set(x, y, PieceKind::O);
+			}
+		}
+	}
+
+	// brings in the next piece and hard drops it straight down
+	fn drop_piece(engine: &mut Engine) {
+		engine.tick(Input::default());
+		assert!(engine.current_piece().is_some(), "the piece should spawn");
+		engine.tick(pressing(Button::HardDrop));
+		assert!(engine.current_piece().is_none());
+	}
+
+	#[test]
+	fn no_room_to_spawn_is_a_block_out() {
+		let mut engine = Engine::new(Rules::default(), 1);
+		stack_from(&mut engine, 0);
+		engine.tick(Input::default());
+		assert!(engine.is_game_over());
+		assert!(engine.current_piece().is_none());
+		assert!(engine.summary().is_some());
+	}
+
+	#[test]
+	fn locking_above_the_visible_rows_is_a_lock_out() {
+		let mut engine = Engine::new(Rules::default(), 1);
+		let hidden_rows = engine.board.hidden_rows();
+		stack_from(&mut engine, hidden_rows);
+		drop_piece(&mut engine);
+		assert!(engine.is_game_over());
+	}
+
+	#[test]
+	fn locking_partly_in_view_carries_on() {
+		let mut engine = Engine::new(Rules::default(), 1);
+		let hidden_rows = engine.board.hidden_rows();
+		stack_from(&mut engine, hidden_rows + 1);
+		drop_piece(&mut engine);
+		assert!(!engine.is_game_over());
+	}
+
+	#[test]
+	fn hold_only_works_once_per_piece() {
+		let mut engine = Engine::new(Rules::default(), 1);
+		engine.tick(Input::default());
+		let first = engine.current_piece().unwrap().kind();
+		let second = engine.queue()[0];
+
+		engine.tick(pressing(Button::Hold));
+		assert_eq!(engine.held_piece(), Some(first));
+		assert_eq!(engine.current_piece().unwrap().kind(), second);
+		assert!(!engine.can_hold());
+
+		// swapping straight back isn't allowed
+		engine.tick(Input::default());
+		engine.tick(pressing(Button::Hold));
+		assert_eq!(engine.held_piece(), Some(first));
+		assert_eq!(engine.current_piece().unwrap().kind(), second);
+
+		// but once that piece locks, the next one can
+		engine.tick(pressing(Button::HardDrop));
+		engine.tick(Input::default());
+		assert!(engine.can_hold());
+		let third = engine.current_piece().unwrap().kind();
+		engine.tick(pressing(Button::Hold));
+		assert_eq!(engine.held_piece(), Some(third));
+		assert_eq!(engine.current_piece().unwrap().kind(), first);
+	}
+
+	#[test]
+	fn gravity_past_the_board_height_just_drops_the_piece() {
+		let mut engine = Engine::new(
+			Rules {
+				gravity: GravityTable::new(vec![(1, u32::MAX)]),
+				handling: Handling {
+					soft_drop_factor: u32::MAX,
+					..Handling::default()
+				},
+				..Rules::default()
+			},
+			1,
+		);
+		engine.tick(Input::default());
+		engine.tick(pressing(Button::SoftDrop));
+		engine.tick(pressing(Button::SoftDrop));
+		assert!(engine.is_grounded());
+	}
+
+	// brings in a piece and sets it down on the floor, without locking it
+	fn ground_piece(engine: &mut Engine) {
+		engine.tick(Input::default());
+		let drop = engine.ghost_offset().unwrap();
+		engine.current_piece.as_mut().unwrap().top_left.y += drop;
+		engine.on_piece_moved();
+		assert!(engine.is_grounded());
+	}
+
+	// how many ticks a grounded piece lasts while being shuffled back and forth every tick
+	fn ticks_to_lock(reset: LockReset, max_resets: u32) -> u32 {
+		let mut engine = Engine::new(
+			Rules {
+				lock_delay: LockDelay {
+					delay: Duration::from_millis(250),
+					reset,
+					max_resets,
+				},
+				..Rules::default()
+			},
+			1,
+		);
+		ground_piece(&mut engine);
+		for ticks in 1.. {
+			let direction = if ticks % 2 == 0 {
+				Button::Right
+			} else {
+				Button::Left
+			};
+			engine.tick(pressing(direction));
+			if engine.current_piece().is_none() {
+				return ticks;
+			}
+		}
+		unreachable!()
+	}
+
+	#[test]
+	fn moving_only_buys_so_much_time() {
+		let delay = ticks_for(Duration::from_millis(250));
+		assert_eq!(ticks_to_lock(LockReset::Step, 3), delay);
+		assert_eq!(ticks_to_lock(LockReset::Move, 0), delay);
+		assert_eq!(ticks_to_lock(LockReset::Move, 3), 3 + delay);
+		assert_eq!(ticks_to_lock(LockReset::Move, 15), 15 + delay);
+	}
+
+	// hangs on to everything the engine tells its observers
+	fn record(engine: &mut Engine) -> Rc<RefCell<Vec<Event>>> {
+		let events = Rc::new(RefCell::new(Vec::new()));
+		let sink = events.clone();
+		engine.observe(move |event: &Event| sink.borrow_mut().push(*event));
+		events
+	}
+
+	#[test]
+	fn pieces_tell_observers_where_they_are() {
+		let mut engine = Engine::new(Rules::default(), 1);
+		let events = record(&mut engine);
+		engine.tick(Input::default());
+		let first = engine.current_piece().unwrap().kind();
+		let second = engine.queue()[0];
+		engine.tick(pressing(Button::Hold));
+		engine.tick(pressing(Button::HardDrop));
+		assert_eq!(
+			*events.borrow(),
+			[
+				Event::PieceSpawned { piece: first },
+				Event::Hold { piece: first },
+				Event::PieceSpawned { piece: second },
+				Event::PieceLocked { piece: second },
+			]
+		);
+
+		events.borrow_mut().clear();
+		engine.notify_paused(true);
+		engine.notify_paused(false);
+		assert_eq!(*events.borrow(), [Event::Paused, Event::Resumed]);
+	}
+
+	// the next lock counts a row that's already full, whatever shape the piece is
+	fn fill_floor(engine: &mut Engine) {
+		let floor = engine.board.height() - 1;
+		for x in 0..engine.board.width() {
+			engine.board.set(x, floor, PieceKind::O);
+		}
+	}
+
+	#[test]
+	fn clears_tell_observers_what_they_were_worth() {
+		let mut engine = Engine::new(
+			Rules {
+				lines_per_level: 1,
+				..Rules::default()
+			},
+			1,
+		);
+		engine.tick(Input::default());
+		let piece = engine.current_piece().unwrap().kind();
+		fill_floor(&mut engine);
+		let events = record(&mut engine);
+		engine.tick(pressing(Button::HardDrop));
+		assert_eq!(
+			*events.borrow(),
+			[
+				Event::PieceLocked { piece },
+				Event::LinesCleared {
+					count: 1,
+					kind: ClearKind::Single,
+				},
+				Event::LevelUp { level: 2 },
+			]
+		);
+	}
+
+	#[test]
+	fn the_end_tells_observers_how_it_went() {
+		let mut engine = Engine::new(Rules::default(), 1);
+		let events = record(&mut engine);
+		stack_from(&mut engine, 0);
+		engine.tick(Input::default());
+		let summary = *engine.summary().unwrap();
+		assert_eq!(*events.borrow(), [Event::GameOver(summary)]);
+	}
+
+	#[test]
+	fn sprints_end_on_the_last_line() {
+		let mut engine = Engine::new(
+			Rules {
+				mode: GameMode::Sprint { lines: 2 },
+				..Rules::default()
+			},
+			1,
+		);
+		for _ in 0..2 {
+			assert!(!engine.is_game_over());
+			// waits out the line clear delay before the next piece shows up
+			while engine.current_piece().is_none() {
+				engine.tick(Input::default());
+			}
+			fill_floor(&mut engine);
+			engine.tick(pressing(Button::HardDrop));
+		}
+		assert!(engine.is_game_over());
+		assert!(engine.did_win());
+		assert_eq!(engine.lines(), 2);
+	}
+
+	// a narrow board fills and clears lines quickly, so there's plenty going on
+	fn busy_rules() -> Rules {
+		Rules {
+			dimensions: Dimensions::new(4, 20, 4),
+			..Rules::default()
+		}
+	}
+
+	// the same made-up player every time, pressing something every few ticks
+	fn input_for(tick: u64) -> Input {
+		let buttons = [
+			Button::Left,
+			Button::Right,
+			Button::RotateClockwise,
+			Button::HardDrop,
+			Button::Hold,
+			Button::RotateCounterClockwise,
+		];
+		let roll = tick.wrapping_mul(6_364_136_223_846_793_005) >> 33;
+		let mut input = Input::default();
+		if roll.is_multiple_of(3) {
+			input.press(buttons[(roll / 3) as usize % buttons.len()]);
+		}
+		input
+	}
+
+	fn play(engine: &mut Engine, ticks: std::ops::Range<u64>) {
+		for tick in ticks {
+			engine.tick(input_for(tick));
+		}
+	}
+
+	// everything that makes one game look different from another
+	fn snapshot(engine: &Engine) -> (u32, u32, u64, bool, Vec<u16>, Vec<PieceKind>) {
+		let board = engine.board();
+		(
+			engine.score(),
+			engine.lines(),
+			engine.ticks(),
+			engine.is_game_over(),
+			(0..board.height()).map(|y| board.row(y)).collect(),
+			engine.queue().iter().copied().collect(),
+		)
+	}
+
+	#[test]
+	fn replays_play_back_the_same_game() {
+		let mut engine = Engine::new(busy_rules(), 7);
+		play(&mut engine, 0..5000);
+		assert!(engine.lines() > 0, "the test game should clear something");
+
+		let mut playback = Playback::new(engine.replay().unwrap().clone());
+		while playback.step() {}
+		assert_eq!(snapshot(playback.engine()), snapshot(&engine));
+		assert_eq!(playback.engine().summary(), engine.summary());
+	}
+
+	// the same piece over and over, which no RandomizerKind would ever do
+	struct OnlyI;
+
+	impl Randomizer for OnlyI {
+		fn next(&mut self) -> PieceKind {
+			PieceKind::I
+		}
+	}
+
+	#[test]
+	fn outside_randomizers_play_but_dont_replay() {
+		let mut engine = Engine::with_randomizer(Rules::default(), Box::new(OnlyI));
+		for _ in 0..3 {
+			drop_piece(&mut engine);
+		}
+		assert!(engine.queue().iter().all(|kind| *kind == PieceKind::I));
+		assert_eq!(engine.replay(), None);
+		#[cfg(feature = "serde")]
+		assert!(serde_json::to_string(&engine).is_err());
+
+		// and there's no seed to start it over with, so it keeps going
+		engine.reset(5);
+		drop_piece(&mut engine);
+		assert_eq!(engine.queue()[0], PieceKind::I);
+	}
+
+	#[test]
+	fn handling_only_changes_before_the_game_starts() {
+		let handling = Handling {
+			das: Duration::from_millis(100),
+			arr: Duration::ZERO,
+			..Handling::default()
+		};
+		let mut engine = Engine::new(busy_rules(), 3);
+		assert!(engine.set_handling(handling));
+		assert_eq!(engine.rules().handling, handling);
+		assert_eq!(engine.replay().unwrap().rules().handling, handling);
+
+		play(&mut engine, 0..10);
+		assert!(!engine.set_handling(Handling::default()));
+		assert_eq!(engine.replay().unwrap().rules().handling, handling);
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn saved_games_carry_on_the_same() {
+		let mut engine = Engine::new(busy_rules(), 11);
+		play(&mut engine, 0..300);
+		assert!(
+			!engine.is_game_over(),
+			"the test game should still be going"
+		);
+		let json = serde_json::to_string(&engine).unwrap();
+		let mut restored: Engine = serde_json::from_str(&json).unwrap();
+		assert_eq!(snapshot(&restored), snapshot(&engine));
+
+		play(&mut engine, 300..3000);
+		play(&mut restored, 300..3000);
+		assert_eq!(snapshot(&restored), snapshot(&engine));
+		assert_eq!(restored.replay(), engine.replay());
+	}
+}
@@ -0,0 +1,77 @@
+// The moments worth telling the outside world about, as they happen. Anything from sound effects
+// to a stats overlay can hang off these without having to pick apart the engine's state
+// every tick.
+use crate::engine::GameSummary;
+use crate::piece::PieceKind;
+use crate::scoring::ClearKind;
+use crate::spin::Spin;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+	feature = "serde",
+	derive(serde::Serialize, serde::Deserialize),
+	serde(tag = "type", rename_all = "snake_case")
+)]
+pub enum Event {
+	// a new piece has appeared at the top of the board
+	PieceSpawned { piece: PieceKind },
+	// the current piece has been sealed into the board
+	PieceLocked { piece: PieceKind },
+	// sent when the lock that fills the rows scores, before they're actually cleared
+	LinesCleared { count: u32, kind: ClearKind },
+	// T-spins, plus spins with every other piece when the rules allow them. Sent whether or not
+	// the spin cleared anything
+	Spin(Spin),
+	// another clear in a row. Not sent for the first one
+	Combo { combo: u32 },
+	LevelUp { level: u32 },
+	// the piece that's just gone into hold
+	Hold { piece: PieceKind },
+	GameOver(GameSummary),
+	// the engine never stops on its own, so these come from whoever's ticking it.
+	// See Engine::notify_paused
+	Paused,
+	Resumed,
+}
+
+impl Event {
+	// every name an event can go by, the same as its "type" once serialized
+	pub const NAMES: [&'static str; 10] = [
+		"piece_spawned",
+		"piece_locked",
+		"lines_cleared",
+		"spin",
+		"combo",
+		"level_up",
+		"hold",
+		"game_over",
+		"paused",
+		"resumed",
+	];
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			Event::PieceSpawned { .. } => "piece_spawned",
+			Event::PieceLocked { .. } => "piece_locked",
+			Event::LinesCleared { .. } => "lines_cleared",
+			Event::Spin(_) => "spin",
+			Event::Combo { .. } => "combo",
+			Event::LevelUp { .. } => "level_up",
+			Event::Hold { .. } => "hold",
+			Event::GameOver(_) => "game_over",
+			Event::Paused => "paused",
+			Event::Resumed => "resumed",
+		}
+	}
+}
+
+// anything that wants to hear about a game as it's played. Closures taking an &Event work too
+pub trait Observer {
+	fn notify(&mut self, event: &Event);
+}
+
+impl<F: FnMut(&Event)> Observer for F {
+	fn notify(&mut self, event: &Event) {
+		self(event)
+	}
+}
@@ -0,0 +1,179 @@
+// Gravity is measured in cells per frame, where a frame is 1/60th of a second no matter how often
+// we're actually ticked. It's fixed point so that every platform falls at exactly the same rate.
+use crate::board::{MAX_HIDDEN_ROWS, MAX_ROWS};
+
+pub const ONE_G: u32 = 65536;
+pub const FRAMES_PER_SECOND: u32 = 60;
+// enough to fall the tallest board there is in a single frame, so nothing's any faster
+pub const MAX_GRAVITY: u32 = (MAX_ROWS + MAX_HIDDEN_ROWS) as u32 * ONE_G;
+
+// how fast pieces fall at each level. Each step applies from its level until the next step
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GravityTable {
+	steps: Vec<(u32, u32)>,
+}
+
+impl GravityTable {
+	// steps are (first level, gravity) pairs, in order of level
+	pub fn new(steps: Vec<(u32, u32)>) -> GravityTable {
+		GravityTable { steps }
+	}
+
+	pub fn steps(&self) -> &[(u32, u32)] {
+		&self.steps
+	}
+
+	pub fn gravity(&self, level: u32) -> u32 {
+		self.steps
+			.iter()
+			.take_while(|(from, _)| *from <= level)
+			.last()
+			.or_else(|| self.steps.first())
+			.map_or(0, |(_, gravity)| (*gravity).min(MAX_GRAVITY))
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RuleSet {
+	#[default]
+	Guideline,
+	Nes,
+	Tgm,
+}
+
+impl RuleSet {
+	pub const ALL: [RuleSet; 3] = [RuleSet::Guideline, RuleSet::Nes, RuleSet::Tgm];
+
+	// the other way round from name
+	pub fn from_name(name: &str) -> Option<RuleSet> {
+		RuleSet::ALL
+			.iter()
+			.copied()
+			.find(|rule_set| rule_set.name() == name)
+	}
+
+	pub fn name(self) -> &'static str {
+		match self {
+			RuleSet::Guideline => "guideline",
+			RuleSet::Nes => "nes",
+			RuleSet::Tgm => "tgm",
+		}
+	}
+
+	pub fn gravity_table(self) -> GravityTable {
+		GravityTable::new(match self {
+			// (0.8 - (level - 1) * 0.007) ^ (level - 1) seconds per row, capped at 20G
+			RuleSet::Guideline => vec![
+				(1, 1092),
+				(2, 1377),
+				(3, 1768),
+				(4, 2311),
+				(5, 3075),
+				(6, 4169),
+				(7, 5759),
+				(8, 8107),
+				(9, 11634),
+				(10, 17026),
+				(11, 25416),
+				(12, 38709),
+				(13, 60169),
+				(14, 95483),
+				(15, 154742),
+				(16, 256187),
+				(17, 433425),
+				(18, 749597),
+				(19, 20 * ONE_G),
+			],
+
+			// NES counts frames per row, and its levels start at 0
+			RuleSet::Nes => vec![
+				(1, ONE_G / 48),
+				(2, ONE_G / 43),
+				(3, ONE_G / 38),
+				(4, ONE_G / 33),
+				(5, ONE_G / 28),
+				(6, ONE_G / 23),
+				(7, ONE_G / 18),
+				(8, ONE_G / 13),
+				(9, ONE_G / 8),
+				(10, ONE_G / 6),
+				(11, ONE_G / 5),
+				(14, ONE_G / 4),
+				(17, ONE_G / 3),
+				(20, ONE_G / 2),
+				(30, ONE_G),
+			],
+
+			// TGM climbs a level for every piece as well as every line, so its internal levels
+			// are squeezed 10:1 onto ours. Note the dip back down at level 21
+			RuleSet::Tgm => vec![
+				(1, 4 * ONE_G / 256),
+				(4, 8 * ONE_G / 256),
+				(5, 10 * ONE_G / 256),
+				(6, 12 * ONE_G / 256),
+				(7, 16 * ONE_G / 256),
+				(8, 32 * ONE_G / 256),
+				(9, 48 * ONE_G / 256),
+				(10, 64 * ONE_G / 256),
+				(11, 80 * ONE_G / 256),
+				(13, 96 * ONE_G / 256),
+				(15, 112 * ONE_G / 256),
+				(17, 128 * ONE_G / 256),
+				(18, 144 * ONE_G / 256),
+				(21, 4 * ONE_G / 256),
+				(23, 32 * ONE_G / 256),
+				(24, 128 * ONE_G / 256),
+				(25, 224 * ONE_G / 256),
+				(26, ONE_G),
+				(31, 2 * ONE_G),
+				(34, 3 * ONE_G),
+				(37, 4 * ONE_G),
+				(41, 5 * ONE_G),
+				(43, 4 * ONE_G),
+				(46, 3 * ONE_G),
+				(51, 20 * ONE_G),
+			],
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn each_step_lasts_until_the_next() {
+		let table = GravityTable::new(vec![(1, 10), (5, 20), (8, 30)]);
+		let gravity: Vec<u32> = (1..=10).map(|level| table.gravity(level)).collect();
+		assert_eq!(gravity, [10, 10, 10, 10, 20, 20, 20, 30, 30, 30]);
+		// anything below the first step gets the first step
+		assert_eq!(table.gravity(0), 10);
+		assert_eq!(GravityTable::new(Vec::new()).gravity(3), 0);
+	}
+
+	#[test]
+	fn rule_sets_follow_their_tables() {
+		let guideline = RuleSet::Guideline.gravity_table();
+		assert_eq!(guideline.gravity(1), 1092);
+		assert_eq!(guideline.gravity(19), 20 * ONE_G);
+		assert_eq!(guideline.gravity(99), 20 * ONE_G);
+
+		let nes = RuleSet::Nes.gravity_table();
+		assert_eq!(nes.gravity(12), ONE_G / 5);
+		assert_eq!(nes.gravity(30), ONE_G);
+
+		let tgm = RuleSet::Tgm.gravity_table();
+		assert_eq!(tgm.gravity(20), 144 * ONE_G / 256);
+		assert_eq!(tgm.gravity(21), 4 * ONE_G / 256);
+	}
+
+	#[test]
+	fn guideline_gravity_never_slows_down() {
+		let table = RuleSet::Guideline.gravity_table();
+		for level in 1..30 {
+			assert!(table.gravity(level + 1) >= table.gravity(level));
+		}
+	}
+}
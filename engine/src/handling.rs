@@ -0,0 +1,221 @@
+// How held keys turn into movement: Delayed Auto Shift, Auto Repeat Rate and soft drop speed.
+// https://tetris.wiki/DAS
+use crate::engine::ticks_for;
+use crate::gravity::MAX_GRAVITY;
+use std::time::Duration;
+
+// a shift big enough to cross the whole board, for when ARR is instant
+const TO_THE_WALL: i32 = 64;
+// past this even the slowest gravity there is drops straight to the floor
+pub const MAX_SOFT_DROP_FACTOR: u32 = MAX_GRAVITY;
+
+// These are the player's preferences more than the game's rules, but they change how a game
+// plays out just the same
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Handling {
+	// how long left or right has to be held before it starts repeating
+	pub das: Duration,
+	// how long between repeats once it has. Zero slides straight to the wall
+	pub arr: Duration,
+	// how many times faster than gravity a held soft drop falls
+	pub soft_drop_factor: u32,
+	// how long auto shift holds off after a rotation, so a charged DAS doesn't drag the freshly
+	// rotated piece away
+	pub das_cut: Duration,
+}
+
+impl Default for Handling {
+	fn default() -> Handling {
+		Handling {
+			das: Duration::from_millis(167),
+			arr: Duration::from_millis(33),
+			soft_drop_factor: 20,
+			das_cut: Duration::from_millis(0),
+		}
+	}
+}
+
+impl Handling {
+	// the same handling, with the soft drop factor somewhere it makes sense
+	pub fn clamped(self) -> Handling {
+		Handling {
+			soft_drop_factor: self.soft_drop_factor.clamp(1, MAX_SOFT_DROP_FACTOR),
+			..self
+		}
+	}
+}
+
+// which way we're auto shifting, and how far along we are. If both directions are held the
+// last one pressed wins
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct AutoShift {
+	is_left_held: bool,
+	is_right_held: bool,
+	direction: i32,
+	charge_ticks: u32,
+	repeat_ticks: u32,
+	cut_ticks: u32,
+}
+
+impl AutoShift {
+	pub(crate) fn press(&mut self, direction: i32) {
+		if direction < 0 {
+			self.is_left_held = true;
+		} else {
+			self.is_right_held = true;
+		}
+		self.start(direction);
+	}
+
+	pub(crate) fn release(&mut self, direction: i32) {
+		if direction < 0 {
+			self.is_left_held = false;
+		} else {
+			self.is_right_held = false;
+		}
+
+		if direction == self.direction {
+			// fall back to the other direction if it's still down, with a fresh charge
+			if self.is_left_held {
+				self.start(-1);
+			} else if self.is_right_held {
+				self.start(1);
+			} else {
+				self.start(0);
+			}
+		}
+	}
+
+	pub(crate) fn cut(&mut self, handling: &Handling) {
+		if handling.das_cut > Duration::from_millis(0) {
+			self.cut_ticks = ticks_for(handling.das_cut);
+		}
+	}
+
+	// how many cells the held direction moves us this tick, if any
+	pub(crate) fn tick(&mut self, handling: &Handling) -> i32 {
+		if self.direction == 0 {
+			return 0;
+		}
+		if self.cut_ticks > 0 {
+			self.cut_ticks -= 1;
+			return 0;
+		}
+
+		self.charge_ticks += 1;
+		let das_ticks = ticks_for(handling.das);
+		if self.charge_ticks < das_ticks {
+			return 0;
+		}
+		if handling.arr == Duration::from_millis(0) {
+			return self.direction * TO_THE_WALL;
+		}
+
+		// the first repeat lands right as DAS charges, the rest every ARR after that
+		if self.charge_ticks == das_ticks {
+			self.repeat_ticks = 0;
+			return self.direction;
+		}
+		self.repeat_ticks += 1;
+		if self.repeat_ticks >= ticks_for(handling.arr) {
+			self.repeat_ticks = 0;
+			return self.direction;
+		}
+		0
+	}
+
+	fn start(&mut self, direction: i32) {
+		self.direction = direction;
+		self.charge_ticks = 0;
+		self.repeat_ticks = 0;
+		self.cut_ticks = 0;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::engine::TICKS_PER_SECOND;
+
+	// 6 ticks of DAS, 3 of ARR and 3 of DAS cut
+	fn handling() -> Handling {
+		assert_eq!(TICKS_PER_SECOND, 60);
+		Handling {
+			das: Duration::from_millis(100),
+			arr: Duration::from_millis(50),
+			das_cut: Duration::from_millis(50),
+			..Handling::default()
+		}
+	}
+
+	fn run(shift: &mut AutoShift, handling: &Handling, ticks: usize) -> Vec<i32> {
+		(0..ticks).map(|_| shift.tick(handling)).collect()
+	}
+
+	#[test]
+	fn repeats_every_arr_once_das_charges() {
+		let handling = handling();
+		let mut shift = AutoShift::default();
+		shift.press(1);
+		assert_eq!(
+			run(&mut shift, &handling, 12),
+			[0, 0, 0, 0, 0, 1, 0, 0, 1, 0, 0, 1]
+		);
+
+		shift.release(1);
+		assert_eq!(run(&mut shift, &handling, 12), [0; 12]);
+	}
+
+	#[test]
+	fn zero_arr_slides_to_the_wall() {
+		let handling = Handling {
+			arr: Duration::ZERO,
+			..handling()
+		};
+		let mut shift = AutoShift::default();
+		shift.press(-1);
+		assert_eq!(
+			run(&mut shift, &handling, 7),
+			[0, 0, 0, 0, 0, -TO_THE_WALL, -TO_THE_WALL]
+		);
+	}
+
+	#[test]
+	fn das_cut_holds_off_after_a_rotation() {
+		let handling = handling();
+		let mut shift = AutoShift::default();
+		shift.press(1);
+		run(&mut shift, &handling, 6);
+		shift.cut(&handling);
+		assert_eq!(run(&mut shift, &handling, 3), [0, 0, 0]);
+		// DAS stays charged through the cut
+		assert!(run(&mut shift, &handling, 3).contains(&1));
+
+		// and with no cut configured, a rotation doesn't interrupt anything
+		let handling = Handling {
+			das_cut: Duration::ZERO,
+			..handling
+		};
+		let mut shift = AutoShift::default();
+		shift.press(1);
+		run(&mut shift, &handling, 6);
+		shift.cut(&handling);
+		assert_eq!(run(&mut shift, &handling, 3), [0, 0, 1]);
+	}
+
+	#[test]
+	fn releasing_falls_back_to_the_other_direction() {
+		let handling = handling();
+		let mut shift = AutoShift::default();
+		shift.press(-1);
+		run(&mut shift, &handling, 3);
+		shift.press(1);
+		assert_eq!(run(&mut shift, &handling, 6), [0, 0, 0, 0, 0, 1]);
+
+		// left is still down, so it takes over with a fresh charge
+		shift.release(1);
+		assert_eq!(run(&mut shift, &handling, 6), [0, 0, 0, 0, 0, -1]);
+	}
+}
@@ -0,0 +1,221 @@
+// What the player's doing, one tick at a time. The engine only ever sees these, so a record of
+// them is all it takes to play a game back exactly.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Button {
+	Left,
+	Right,
+	SoftDrop,
+	HardDrop,
+	RotateClockwise,
+	RotateCounterClockwise,
+	Rotate180,
+	Hold,
+}
+
+impl Button {
+	pub const ALL: [Button; 8] = [
+		Button::Left,
+		Button::Right,
+		Button::SoftDrop,
+		Button::HardDrop,
+		Button::RotateClockwise,
+		Button::RotateCounterClockwise,
+		Button::Rotate180,
+		Button::Hold,
+	];
+
+	fn bit(self) -> u8 {
+		1 << (self as u8)
+	}
+}
+
+// which buttons are down during a tick, and which were pressed since the last one. Presses are
+// kept separately so that a tap that's down and back up between two ticks still counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Input {
+	held: u8,
+	pressed: u8,
+}
+
+impl Input {
+	pub fn from_bits(held: u8, pressed: u8) -> Input {
+		Input { held, pressed }
+	}
+
+	pub fn held_bits(self) -> u8 {
+		self.held
+	}
+
+	pub fn pressed_bits(self) -> u8 {
+		self.pressed
+	}
+
+	pub fn is_held(self, button: Button) -> bool {
+		self.held & button.bit() != 0
+	}
+
+	pub fn was_pressed(self, button: Button) -> bool {
+		self.pressed & button.bit() != 0
+	}
+
+	pub fn press(&mut self, button: Button) {
+		self.held |= button.bit();
+		self.pressed |= button.bit();
+	}
+
+	pub fn release(&mut self, button: Button) {
+		self.held &= !button.bit();
+	}
+
+	// what's left once a tick has used this up: anything still held stays held, but presses
+	// only count once
+	pub fn next(self) -> Input {
+		Input {
+			held: self.held,
+			pressed: 0,
+		}
+	}
+}
+
+// gathers up what the player does between ticks. An Input only has room for one press of each
+// button, so any more than that (a quick double tap on rotate, say) are carried over to the
+// ticks that follow instead of being lost
+#[derive(Debug, Clone, Default)]
+pub struct InputBuffer {
+	input: Input,
+	// presses still owed to later ticks, per button
+	extra_presses: [u8; Button::ALL.len()],
+}
+
+impl InputBuffer {
+	pub fn press(&mut self, button: Button) {
+		if self.input.was_pressed(button) {
+			let extra = &mut self.extra_presses[button as usize];
+			*extra = extra.saturating_add(1);
+		}
+		// whether or not the press itself is owed, the key's down again now
+		self.input.press(button);
+	}
+
+	pub fn release(&mut self, button: Button) {
+		self.input.release(button);
+	}
+
+	// hands over the input for this tick, and brings in the next of any presses still owed
+	pub fn take(&mut self) -> Input {
+		let input = self.input;
+		self.input = input.next();
+		for button in Button::ALL.iter().copied() {
+			let extra = &mut self.extra_presses[button as usize];
+			if *extra > 0 {
+				*extra -= 1;
+				// just the press: the key might well be back up by now, and holding it for the
+				// player would leave it stuck down
+				self.input.pressed |= button.bit();
+			}
+		}
+		input
+	}
+
+	// drops every press, carried over or not, while keeping whatever's held down
+	pub fn forget_presses(&mut self) {
+		self.input = self.input.next();
+		self.extra_presses = Default::default();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn a_tap_between_ticks_still_counts_once() {
+		let mut input = Input::default();
+		input.press(Button::HardDrop);
+		input.release(Button::HardDrop);
+		assert!(input.was_pressed(Button::HardDrop));
+		assert!(!input.is_held(Button::HardDrop));
+		assert!(!input.next().was_pressed(Button::HardDrop));
+	}
+
+	#[test]
+	fn held_buttons_carry_over() {
+		let mut input = Input::default();
+		input.press(Button::Left);
+		let next = input.next();
+		assert!(next.is_held(Button::Left));
+		assert!(!next.was_pressed(Button::Left));
+		assert_eq!(
+			Input::from_bits(next.held_bits(), next.pressed_bits()),
+			next
+		);
+	}
+
+	#[test]
+	fn double_taps_carry_over_to_the_next_tick() {
+		let mut buffer = InputBuffer::default();
+		buffer.press(Button::RotateClockwise);
+		buffer.release(Button::RotateClockwise);
+		buffer.press(Button::RotateClockwise);
+		buffer.release(Button::RotateClockwise);
+
+		let first = buffer.take();
+		assert!(first.was_pressed(Button::RotateClockwise));
+		assert!(!first.is_held(Button::RotateClockwise));
+
+		let second = buffer.take();
+		assert!(second.was_pressed(Button::RotateClockwise));
+		assert!(!second.is_held(Button::RotateClockwise));
+
+		assert_eq!(buffer.take(), Input::default());
+	}
+
+	#[test]
+	fn pressing_again_holds_the_button_again() {
+		let mut buffer = InputBuffer::default();
+		buffer.press(Button::Left);
+		buffer.release(Button::Left);
+		buffer.press(Button::Left);
+
+		let first = buffer.take();
+		assert!(first.was_pressed(Button::Left));
+		assert!(first.is_held(Button::Left));
+
+		// the second press is still owed, and the key hasn't come back up since
+		let second = buffer.take();
+		assert!(second.was_pressed(Button::Left));
+		assert!(second.is_held(Button::Left));
+
+		let third = buffer.take();
+		assert!(!third.was_pressed(Button::Left));
+		assert!(third.is_held(Button::Left));
+	}
+
+	#[test]
+	fn held_buttons_stay_held_without_pressing_again() {
+		let mut buffer = InputBuffer::default();
+		buffer.press(Button::Left);
+		assert!(buffer.take().was_pressed(Button::Left));
+
+		let next = buffer.take();
+		assert!(next.is_held(Button::Left));
+		assert!(!next.was_pressed(Button::Left));
+	}
+
+	#[test]
+	fn forgetting_presses_keeps_whats_held() {
+		let mut buffer = InputBuffer::default();
+		buffer.press(Button::SoftDrop);
+		buffer.press(Button::Hold);
+		buffer.press(Button::Hold);
+		buffer.release(Button::Hold);
+		buffer.forget_presses();
+
+		let input = buffer.take();
+		assert!(input.is_held(Button::SoftDrop));
+		assert_eq!(input.pressed_bits(), 0);
+		assert_eq!(buffer.take().pressed_bits(), 0);
+	}
+}
@@ -0,0 +1,38 @@
+//! The rules of the game, with no knowledge of browsers or canvases. The web build in the parent
+//! crate is just a frontend over this, so anything that runs here natively (bots, replays, tests)
+//! sees exactly the same game.
+
+mod board;
+mod engine;
+mod event;
+mod gravity;
+mod handling;
+mod input;
+mod piece;
+mod randomizer;
+mod replay;
+mod replay_format;
+mod rules;
+mod scoring;
+mod spin;
+mod srs;
+
+pub use board::{Board, Dimensions, MAX_COLS, MAX_HIDDEN_ROWS, MAX_ROWS};
+pub use engine::{Engine, GameSummary, SAVE_VERSION, TICKS_PER_SECOND};
+pub use event::{Event, Observer};
+pub use gravity::{GravityTable, RuleSet, FRAMES_PER_SECOND, MAX_GRAVITY, ONE_G};
+pub use handling::{Handling, MAX_SOFT_DROP_FACTOR};
+pub use input::{Button, Input, InputBuffer};
+pub use piece::{Piece, PieceKind, Vector2D};
+pub use randomizer::{
+	BagRandomizer, HistoryRandomizer, PureRandomizer, Randomizer, RandomizerKind, Rng,
+};
+pub use replay::{Playback, Replay};
+pub use replay_format::{ReplayError, ENGINE_VERSION, FORMAT_VERSION};
+pub use rules::{
+	GameMode, LockDelay, LockReset, Rules, DEFAULT_SPRINT_LINES, MAX_LOCK_DELAY, MAX_LOCK_RESETS,
+	MAX_PREVIEW_LEN,
+};
+pub use scoring::{ClearKind, LineClear, Scoring};
+pub use spin::{Spin, SpinKind};
+pub use srs::{Kick, Rotation, RotationDirection};
@@ -0,0 +1,190 @@
+use crate::board::Dimensions;
+use crate::srs::Rotation;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PieceKind {
+	I,
+	O,
+	T,
+	S,
+	Z,
+	J,
+	L,
+}
+
+impl fmt::Display for PieceKind {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let letter = match self {
+			PieceKind::I => "I",
+			PieceKind::O => "O",
+			PieceKind::T => "T",
+			PieceKind::S => "S",
+			PieceKind::Z => "Z",
+			PieceKind::J => "J",
+			PieceKind::L => "L",
+		};
+		write!(f, "{}", letter)
+	}
+}
+
+impl PieceKind {
+	pub const ALL: [PieceKind; 7] = [
+		PieceKind::I,
+		PieceKind::T,
+		PieceKind::S,
+		PieceKind::Z,
+		PieceKind::O,
+		PieceKind::J,
+		PieceKind::L,
+	];
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vector2D {
+	pub x: i32,
+	pub y: i32,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Piece {
+	pub(crate) top_left: Vector2D,
+	pub(crate) size: i32,
+	pub(crate) squares: Vec<Vector2D>, // square offsets from top_left
+	pub(crate) kind: PieceKind,
+	pub(crate) rotation: Rotation,
+}
+
+impl Piece {
+	pub fn kind(&self) -> PieceKind {
+		self.kind
+	}
+
+	pub fn rotation(&self) -> Rotation {
+		self.rotation
+	}
+
+	// square offsets within the piece's bounding box
+	pub fn squares(&self) -> &[Vector2D] {
+		&self.squares
+	}
+
+	// absolute board positions of each square in the piece
+	pub fn cells(&self) -> impl Iterator<Item = Vector2D> + '_ {
+		self.squares.iter().map(move |square| Vector2D {
+			x: self.top_left.x + square.x,
+			y: self.top_left.y + square.y,
+		})
+	}
+
+	pub(crate) fn rotate_counter_clockwise(&mut self) {
+		for square in self.squares.iter_mut() {
+			// flip about the y-axis
+			square.x = self.size - 1 - square.x;
+
+			// translate about the origin
+			std::mem::swap(&mut square.x, &mut square.y);
+		}
+	}
+
+	pub(crate) fn rotate_clockwise(&mut self) {
+		for square in self.squares.iter_mut() {
+			// flip about the x-axis
+			square.y = self.size - 1 - square.y;
+
+			// translate about the origin
+			std::mem::swap(&mut square.x, &mut square.y);
+		}
+	}
+
+	// every piece spawns flat side down, centered, in the bottom two of the hidden rows just
+	// above what the player can see
+	pub fn spawn(kind: PieceKind, dimensions: Dimensions) -> Piece {
+		let mut piece = Piece::new(kind);
+		piece.top_left = Vector2D {
+			x: (dimensions.cols - piece.size) / 2,
+			y: dimensions.hidden_rows - 2,
+		};
+		piece
+	}
+
+	// in its spawn orientation, up in the top left corner
+	pub fn new(kind: PieceKind) -> Piece {
+		let (size, squares) = match kind {
+			PieceKind::I => (
+				4,
+				vec![
+					Vector2D { x: 0, y: 1 },
+					Vector2D { x: 1, y: 1 },
+					Vector2D { x: 2, y: 1 },
+					Vector2D { x: 3, y: 1 },
+				],
+			),
+			PieceKind::T => (
+				3,
+				vec![
+					Vector2D { x: 1, y: 0 },
+					Vector2D { x: 0, y: 1 },
+					Vector2D { x: 1, y: 1 },
+					Vector2D { x: 2, y: 1 },
+				],
+			),
+			PieceKind::S => (
+				3,
+				vec![
+					Vector2D { x: 1, y: 0 },
+					Vector2D { x: 2, y: 0 },
+					Vector2D { x: 0, y: 1 },
+					Vector2D { x: 1, y: 1 },
+				],
+			),
+			PieceKind::Z => (
+				3,
+				vec![
+					Vector2D { x: 0, y: 0 },
+					Vector2D { x: 1, y: 0 },
+					Vector2D { x: 1, y: 1 },
+					Vector2D { x: 2, y: 1 },
+				],
+			),
+			PieceKind::O => (
+				2,
+				vec![
+					Vector2D { x: 0, y: 0 },
+					Vector2D { x: 0, y: 1 },
+					Vector2D { x: 1, y: 0 },
+					Vector2D { x: 1, y: 1 },
+				],
+			),
+			PieceKind::J => (
+				3,
+				vec![
+					Vector2D { x: 0, y: 0 },
+					Vector2D { x: 0, y: 1 },
+					Vector2D { x: 1, y: 1 },
+					Vector2D { x: 2, y: 1 },
+				],
+			),
+			PieceKind::L => (
+				3,
+				vec![
+					Vector2D { x: 2, y: 0 },
+					Vector2D { x: 0, y: 1 },
+					Vector2D { x: 1, y: 1 },
+					Vector2D { x: 2, y: 1 },
+				],
+			),
+		};
+
+		Piece {
+			kind,
+			top_left: Vector2D::default(),
+			size,
+			squares,
+			rotation: Rotation::Spawn,
+		}
+	}
+}
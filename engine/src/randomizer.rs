@@ -0,0 +1,297 @@
+use crate::piece::PieceKind;
+#[cfg(feature = "serde")]
+use crate::replay_format::MAX_TICKS;
+
+// small, portable PRNG (splitmix64). We don't lean on rand here since its generators aren't
+// guaranteed to give the same sequence across versions, and replays need exactly that
+#[derive(Debug, Clone)]
+pub struct Rng {
+	state: u64,
+}
+
+impl Rng {
+	pub fn new(seed: u64) -> Rng {
+		Rng { state: seed }
+	}
+
+	pub fn next_u64(&mut self) -> u64 {
+		self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+		let mut z = self.state;
+		z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+		z ^ (z >> 31)
+	}
+
+	// uniform in 0..n
+	pub fn below(&mut self, n: usize) -> usize {
+		// rejection sampling, so we don't favor the low numbers
+		let n = n as u64;
+		let zone = u64::MAX - u64::MAX % n;
+		loop {
+			let value = self.next_u64();
+			if value < zone {
+				return (value % n) as usize;
+			}
+		}
+	}
+}
+
+// anything that can hand out an endless stream of pieces. Any of these can drive an engine (see
+// Engine::with_randomizer), but only the RandomizerKinds can be replayed or saved
+pub trait Randomizer {
+	fn next(&mut self) -> PieceKind;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RandomizerKind {
+	// every piece once, shuffled, then repeat
+	#[default]
+	Bag,
+	// every piece is an independent roll
+	Pure,
+	// TGM-style: reroll a few times to avoid anything in the last four pieces
+	History,
+}
+
+impl RandomizerKind {
+	pub fn create(self, seed: u64) -> Box<dyn Randomizer> {
+		match self {
+			RandomizerKind::Bag => Box::new(BagRandomizer::new(seed)),
+			RandomizerKind::Pure => Box::new(PureRandomizer::new(seed)),
+			RandomizerKind::History => Box::new(HistoryRandomizer::new(seed)),
+		}
+	}
+}
+
+// hands out the game's pieces, keeping track of how many so far. A built-in randomizer can be
+// rebuilt exactly from its kind, seed and count, which is what replays and saves rely on
+pub(crate) struct PieceSource {
+	// None for a randomizer from outside, which we've no way of rebuilding
+	origin: Option<(RandomizerKind, u64)>,
+	drawn: u64,
+	randomizer: Box<dyn Randomizer>,
+}
+
+impl PieceSource {
+	pub(crate) fn new(kind: RandomizerKind, seed: u64) -> PieceSource {
+		PieceSource {
+			origin: Some((kind, seed)),
+			drawn: 0,
+			randomizer: kind.create(seed),
+		}
+	}
+
+	pub(crate) fn custom(randomizer: Box<dyn Randomizer>) -> PieceSource {
+		PieceSource {
+			origin: None,
+			drawn: 0,
+			randomizer,
+		}
+	}
+
+	// whether this is one of the RandomizerKinds, and so can go in a replay or a save
+	pub(crate) fn is_rebuildable(&self) -> bool {
+		self.origin.is_some()
+	}
+
+	pub(crate) fn next(&mut self) -> PieceKind {
+		self.drawn += 1;
+		self.randomizer.next()
+	}
+}
+
+// a piece and a hold every tick for a day of play, the same as the longest replay. No real game
+// gets near it, and rebuilding a source that claims more could hang whoever's loading it
+#[cfg(feature = "serde")]
+const MAX_DRAWN: u64 = MAX_TICKS * 2;
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SavedPieceSource {
+	kind: RandomizerKind,
+	seed: u64,
+	drawn: u64,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for PieceSource {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		let (kind, seed) = match self.origin {
+			Some(origin) => origin,
+			None => {
+				return Err(serde::ser::Error::custom(
+					"only games using a RandomizerKind can be saved",
+				))
+			}
+		};
+		SavedPieceSource {
+			kind,
+			seed,
+			drawn: self.drawn,
+		}
+		.serialize(serializer)
+	}
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PieceSource {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<PieceSource, D::Error> {
+		let saved = SavedPieceSource::deserialize(deserializer)?;
+		if saved.drawn > MAX_DRAWN {
+			return Err(serde::de::Error::custom("too many pieces drawn"));
+		}
+		// replaying the draws is far simpler than saving every randomizer's insides
+		let mut source = PieceSource::new(saved.kind, saved.seed);
+		for _ in 0..saved.drawn {
+			source.next();
+		}
+		Ok(source)
+	}
+}
+
+pub struct BagRandomizer {
+	rng: Rng,
+	bag: Vec<PieceKind>,
+}
+
+impl BagRandomizer {
+	pub fn new(seed: u64) -> BagRandomizer {
+		BagRandomizer {
+			rng: Rng::new(seed),
+			bag: Vec::with_capacity(PieceKind::ALL.len()),
+		}
+	}
+
+	fn refill(&mut self) {
+		self.bag.extend_from_slice(&PieceKind::ALL);
+		// fisher-yates
+		for i in (1..self.bag.len()).rev() {
+			let j = self.rng.below(i + 1);
+			self.bag.swap(i, j);
+		}
+	}
+}
+
+impl Randomizer for BagRandomizer {
+	fn next(&mut self) -> PieceKind {
+		if self.bag.is_empty() {
+			self.refill();
+		}
+		self.bag.pop().unwrap()
+	}
+}
+
+pub struct PureRandomizer {
+	rng: Rng,
+}
+
+impl PureRandomizer {
+	pub fn new(seed: u64) -> PureRandomizer {
+		PureRandomizer {
+			rng: Rng::new(seed),
+		}
+	}
+}
+
+impl Randomizer for PureRandomizer {
+	fn next(&mut self) -> PieceKind {
+		PieceKind::ALL[self.rng.below(PieceKind::ALL.len())]
+	}
+}
+
+const HISTORY_ROLLS: usize = 4;
+
+pub struct HistoryRandomizer {
+	rng: Rng,
+	history: [PieceKind; 4],
+	is_first: bool,
+}
+
+impl HistoryRandomizer {
+	pub fn new(seed: u64) -> HistoryRandomizer {
+		HistoryRandomizer {
+			rng: Rng::new(seed),
+			history: [PieceKind::Z; 4],
+			is_first: true,
+		}
+	}
+}
+
+impl Randomizer for HistoryRandomizer {
+	fn next(&mut self) -> PieceKind {
+		let piece = if self.is_first {
+			// never start on something that forces an overhang
+			self.is_first = false;
+			let safe_starts = [PieceKind::I, PieceKind::J, PieceKind::L, PieceKind::T];
+			safe_starts[self.rng.below(safe_starts.len())]
+		} else {
+			let mut piece = PieceKind::ALL[self.rng.below(PieceKind::ALL.len())];
+			for _ in 1..HISTORY_ROLLS {
+				if !self.history.contains(&piece) {
+					break;
+				}
+				piece = PieceKind::ALL[self.rng.below(PieceKind::ALL.len())];
+			}
+			piece
+		};
+
+		self.history.rotate_right(1);
+		self.history[0] = piece;
+		piece
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const KINDS: [RandomizerKind; 3] = [
+		RandomizerKind::Bag,
+		RandomizerKind::Pure,
+		RandomizerKind::History,
+	];
+
+	fn draw(randomizer: &mut dyn Randomizer, count: usize) -> Vec<PieceKind> {
+		(0..count).map(|_| randomizer.next()).collect()
+	}
+
+	#[test]
+	fn every_bag_holds_each_piece_once() {
+		let mut randomizer = BagRandomizer::new(5);
+		for bag in draw(&mut randomizer, 7 * 100).chunks(7) {
+			for kind in PieceKind::ALL {
+				assert_eq!(bag.iter().filter(|drawn| **drawn == kind).count(), 1);
+			}
+		}
+	}
+
+	#[test]
+	fn the_same_seed_gives_the_same_pieces() {
+		for kind in KINDS {
+			let first = draw(kind.create(99).as_mut(), 200);
+			assert_eq!(draw(kind.create(99).as_mut(), 200), first, "{:?}", kind);
+			assert_ne!(draw(kind.create(100).as_mut(), 200), first, "{:?}", kind);
+		}
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn saves_cant_claim_endless_draws() {
+		let mut source = PieceSource::new(RandomizerKind::Bag, 3);
+		source.next();
+		let json = serde_json::to_string(&source).unwrap();
+		assert!(serde_json::from_str::<PieceSource>(&json).is_ok());
+
+		let endless = json.replace("\"drawn\":1", &format!("\"drawn\":{}", u64::MAX));
+		assert!(serde_json::from_str::<PieceSource>(&endless).is_err());
+	}
+
+	#[test]
+	fn history_never_starts_on_an_overhang() {
+		for seed in 0..100 {
+			let first = HistoryRandomizer::new(seed).next();
+			assert!(!matches!(first, PieceKind::S | PieceKind::Z | PieceKind::O));
+		}
+	}
+}
@@ -0,0 +1,107 @@
+// The engine is deterministic, so a game is nothing more than its rules, its seed and what the
+// player did on every tick. Play those back and you get exactly the same game.
+use crate::engine::Engine;
+use crate::input::Input;
+use crate::rules::Rules;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replay {
+	rules: Rules,
+	seed: u64,
+	inputs: Vec<Input>,
+}
+
+impl Replay {
+	pub fn new(rules: Rules, seed: u64) -> Replay {
+		Replay {
+			rules,
+			seed,
+			inputs: Vec::new(),
+		}
+	}
+
+	pub(crate) fn from_inputs(rules: Rules, seed: u64, inputs: Vec<Input>) -> Replay {
+		Replay {
+			rules,
+			seed,
+			inputs,
+		}
+	}
+
+	pub fn rules(&self) -> &Rules {
+		&self.rules
+	}
+
+	pub fn seed(&self) -> u64 {
+		self.seed
+	}
+
+	// one per tick, in order
+	pub fn inputs(&self) -> &[Input] {
+		&self.inputs
+	}
+
+	pub fn len(&self) -> usize {
+		self.inputs.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.inputs.is_empty()
+	}
+
+	pub fn record(&mut self, input: Input) {
+		self.inputs.push(input);
+	}
+}
+
+// an engine that gets its inputs from a replay rather than a player
+pub struct Playback {
+	replay: Replay,
+	engine: Engine,
+	position: usize,
+}
+
+impl Playback {
+	pub fn new(replay: Replay) -> Playback {
+		Playback {
+			engine: Engine::new(replay.rules.clone(), replay.seed),
+			replay,
+			position: 0,
+		}
+	}
+
+	pub fn replay(&self) -> &Replay {
+		&self.replay
+	}
+
+	pub fn engine(&self) -> &Engine {
+		&self.engine
+	}
+
+	// how many ticks in we are
+	pub fn position(&self) -> usize {
+		self.position
+	}
+
+	pub fn is_finished(&self) -> bool {
+		self.position >= self.replay.len()
+	}
+
+	// plays the next tick. Returns false once there's nothing left to play
+	pub fn step(&mut self) -> bool {
+		match self.replay.inputs.get(self.position) {
+			Some(input) => {
+				self.engine.tick(*input);
+				self.position += 1;
+				true
+			}
+			None => false,
+		}
+	}
+
+	// back to the very start
+	pub fn rewind(&mut self) {
+		self.engine = Engine::new(self.replay.rules.clone(), self.replay.seed);
+		self.position = 0;
+	}
+}
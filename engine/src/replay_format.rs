@@ -0,0 +1,684 @@
+// Replays as files, small enough to share as a link. Everything's little-endian:
+//
+//   magic      "RTRP"
+//   format     u16, FORMAT_VERSION: how the rest of these bytes are laid out
+//   engine     u16, ENGINE_VERSION: which version of the rules the game was played under
+//   rules      rule set u8, columns u8, visible rows u16, hidden rows u8, randomizer u8, gravity step count u16 then (level u32, gravity u32)
+//              for each (or a count of 0xffff and no steps for the rule set's own table), lines
+//              per level u32, preview length u8, lock delay ms u32, lock reset u8, max lock
+//              resets u32, all spin u8, DAS ms u32, ARR ms u32, soft drop factor u32, DAS cut
+//              ms u32, mode u8 then (for sprints) the line goal u32
+//   seed       u64
+//   ticks      varint, how many inputs there are in total
+//   runs       until every tick's accounted for: varint run length, then held u8 and pressed u8
+//              for an input that repeats that many times in a row
+//   checksum   u32, FNV-1a over everything before it
+//
+// Varints are LEB128: seven bits at a time, lowest first, with the top bit set on all but the
+// last byte. Most ticks are just the same keys held down as the tick before, so runs get long.
+//
+// The text form is the same bytes in URL-safe base64, without padding.
+use crate::board::Dimensions;
+use crate::engine::TICKS_PER_SECOND;
+use crate::gravity::{GravityTable, RuleSet, MAX_GRAVITY};
+use crate::handling::Handling;
+use crate::input::Input;
+use crate::randomizer::RandomizerKind;
+use crate::replay::Replay;
+use crate::rules::{GameMode, LockDelay, LockReset, Rules};
+use std::fmt;
+use std::time::Duration;
+
+const MAGIC: &[u8; 4] = b"RTRP";
+
+// bump whenever the layout above changes
+pub const FORMAT_VERSION: u16 = 1;
+
+// bump whenever a change to the engine means an old replay would play out differently. There's
+// no playing those back faithfully, so they're turned away rather than shown going wrong
+pub const ENGINE_VERSION: u16 = 1;
+
+// a full day of play, far longer than any real game. Anything claiming more is turned away before
+// we try to make room for it
+pub(crate) const MAX_TICKS: u64 = TICKS_PER_SECOND as u64 * 60 * 60 * 24;
+
+// in place of a gravity step count, for the table that comes with the rule set
+const DEFAULT_GRAVITY: u16 = u16::MAX;
+
+const BASE64_ALPHABET: &[u8; 64] =
+	b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayError {
+	// not a replay at all
+	NotAReplay,
+	// laid out some other way than we know how to read
+	UnsupportedFormat { found: u16, supported: u16 },
+	// recorded under different rules of play, so it wouldn't play back the same
+	WrongEngineVersion { found: u16, expected: u16 },
+	// cut off partway through
+	Truncated,
+	// something got changed or corrupted along the way
+	BadChecksum,
+	// a field that doesn't hold anything we recognize
+	InvalidValue(&'static str),
+	// the text form has characters base64 doesn't use
+	InvalidText,
+}
+
+impl fmt::Display for ReplayError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ReplayError::NotAReplay => write!(f, "this isn't a replay file"),
+			ReplayError::UnsupportedFormat { found, supported } => write!(
+				f,
+				"this replay uses format version {}, but only version {} is supported",
+				found, supported
+			),
+			ReplayError::WrongEngineVersion { found, expected } => write!(
+				f,
+				"this replay was recorded with engine version {}, but this is version {}",
+				found, expected
+			),
+			ReplayError::Truncated => write!(f, "this replay is incomplete"),
+			ReplayError::BadChecksum => write!(f, "this replay is corrupted"),
+			ReplayError::InvalidValue(field) => {
+				write!(f, "this replay has an invalid {}", field)
+			}
+			ReplayError::InvalidText => write!(f, "this isn't a valid replay code"),
+		}
+	}
+}
+
+impl std::error::Error for ReplayError {}
+
+impl Replay {
+	pub fn encode(&self) -> Vec<u8> {
+		let mut writer = Writer::default();
+		writer.bytes(MAGIC);
+		writer.u16(FORMAT_VERSION);
+		writer.u16(ENGINE_VERSION);
+		write_rules(&mut writer, self.rules());
+		writer.u64(self.seed());
+
+		let inputs = self.inputs();
+		writer.varint(inputs.len() as u64);
+		let mut index = 0;
+		while index < inputs.len() {
+			let input = inputs[index];
+			let run = inputs[index..]
+				.iter()
+				.take_while(|other| **other == input)
+				.count();
+			writer.varint(run as u64);
+			writer.u8(input.held_bits());
+			writer.u8(input.pressed_bits());
+			index += run;
+		}
+
+		let checksum = fnv1a(&writer.bytes);
+		writer.u32(checksum);
+		writer.bytes
+	}
+
+	pub fn decode(bytes: &[u8]) -> Result<Replay, ReplayError> {
+		if bytes.len() < MAGIC.len() || &bytes[..MAGIC.len()] != MAGIC {
+			return Err(ReplayError::NotAReplay);
+		}
+
+		// the version comes before the checksum, since a newer format might not have one there
+		let mut reader = Reader::new(&bytes[MAGIC.len()..]);
+		let format = reader.u16()?;
+		if format != FORMAT_VERSION {
+			return Err(ReplayError::UnsupportedFormat {
+				found: format,
+				supported: FORMAT_VERSION,
+			});
+		}
+
+		if reader.remaining() < 4 {
+			return Err(ReplayError::Truncated);
+		}
+		let (body, checksum) = bytes.split_at(bytes.len() - 4);
+		if fnv1a(body) != Reader::new(checksum).u32()? {
+			return Err(ReplayError::BadChecksum);
+		}
+
+		let mut reader = Reader::new(&body[MAGIC.len() + 2..]);
+		let engine = reader.u16()?;
+		if engine != ENGINE_VERSION {
+			return Err(ReplayError::WrongEngineVersion {
+				found: engine,
+				expected: ENGINE_VERSION,
+			});
+		}
+		let rules = read_rules(&mut reader)?;
+		let seed = reader.u64()?;
+
+		let ticks = reader.varint()?;
+		if ticks > MAX_TICKS {
+			return Err(ReplayError::InvalidValue("tick count"));
+		}
+		let ticks = ticks as usize;
+		// every run takes at least three bytes, so there's no need to trust the count any further
+		let mut inputs = Vec::with_capacity(ticks.min(reader.remaining() * 128));
+		while inputs.len() < ticks {
+			// checked before it's a usize, which is only 32 bits on the web
+			let run = reader.varint()?;
+			let input = Input::from_bits(reader.u8()?, reader.u8()?);
+			if run == 0 || run > (ticks - inputs.len()) as u64 {
+				return Err(ReplayError::InvalidValue("input run"));
+			}
+			inputs.extend(std::iter::repeat_n(input, run as usize));
+		}
+		if reader.remaining() > 0 {
+			return Err(ReplayError::InvalidValue("length"));
+		}
+
+		Ok(Replay::from_inputs(rules, seed, inputs))
+	}
+
+	// the same as encode, but safe to paste into a URL
+	pub fn to_text(&self) -> String {
+		base64_encode(&self.encode())
+	}
+
+	pub fn from_text(text: &str) -> Result<Replay, ReplayError> {
+		Replay::decode(&base64_decode(text.trim())?)
+	}
+}
+
+// saved games carry their replay along in its compact text form, rather than as thousands of
+// separate inputs
+#[cfg(feature = "serde")]
+impl serde::Serialize for Replay {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(&self.to_text())
+	}
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Replay {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Replay, D::Error> {
+		let text = String::deserialize(deserializer)?;
+		Replay::from_text(&text).map_err(serde::de::Error::custom)
+	}
+}
+
+fn write_rules(writer: &mut Writer, rules: &Rules) {
+	writer.u8(match rules.rule_set {
+		RuleSet::Guideline => 0,
+		RuleSet::Nes => 1,
+		RuleSet::Tgm => 2,
+	});
+	// clamping keeps each of these well inside its field
+	let dimensions = rules.dimensions.clamped();
+	writer.u8(dimensions.cols as u8);
+	writer.u16(dimensions.visible_rows as u16);
+	writer.u8(dimensions.hidden_rows as u8);
+	writer.u8(match rules.randomizer {
+		RandomizerKind::Bag => 0,
+		RandomizerKind::Pure => 1,
+		RandomizerKind::History => 2,
+	});
+
+	if rules.gravity == rules.rule_set.gravity_table() {
+		writer.u16(DEFAULT_GRAVITY);
+	} else {
+		let steps = &rules.gravity.steps()[..rules
+			.gravity
+			.steps()
+			.len()
+			.min(DEFAULT_GRAVITY as usize - 1)];
+		writer.u16(steps.len() as u16);
+		for (level, gravity) in steps {
+			writer.u32(*level);
+			// anything faster falls just the same
+			writer.u32((*gravity).min(MAX_GRAVITY));
+		}
+	}
+
+	writer.u32(rules.lines_per_level);
+	writer.u8(rules.preview_len as u8);
+	// the engine clamps these too, so the game plays out the same
+	let lock_delay = rules.lock_delay.clamped();
+	writer.duration(lock_delay.delay);
+	writer.u8(match lock_delay.reset {
+		LockReset::Step => 0,
+		LockReset::Move => 1,
+	});
+	writer.u32(lock_delay.max_resets);
+	writer.u8(rules.all_spin as u8);
+
+	let handling = rules.handling.clamped();
+	writer.duration(handling.das);
+	writer.duration(handling.arr);
+	writer.u32(handling.soft_drop_factor);
+	writer.duration(handling.das_cut);
+
+	match rules.mode {
+		GameMode::Marathon => writer.u8(0),
+		GameMode::Sprint { lines } => {
+			writer.u8(1);
+			writer.u32(lines);
+		}
+	}
+}
+
+fn read_rules(reader: &mut Reader) -> Result<Rules, ReplayError> {
+	let rule_set = match reader.u8()? {
+		0 => RuleSet::Guideline,
+		1 => RuleSet::Nes,
+		2 => RuleSet::Tgm,
+		_ => return Err(ReplayError::InvalidValue("rule set")),
+	};
+	let dimensions = Dimensions {
+		cols: reader.u8()? as i32,
+		visible_rows: reader.u16()? as i32,
+		hidden_rows: reader.u8()? as i32,
+	};
+	if dimensions.clamped() != dimensions {
+		return Err(ReplayError::InvalidValue("board size"));
+	}
+	let randomizer = match reader.u8()? {
+		0 => RandomizerKind::Bag,
+		1 => RandomizerKind::Pure,
+		2 => RandomizerKind::History,
+		_ => return Err(ReplayError::InvalidValue("randomizer")),
+	};
+
+	let step_count = reader.u16()?;
+	let gravity = if step_count == DEFAULT_GRAVITY {
+		rule_set.gravity_table()
+	} else {
+		let mut steps = Vec::with_capacity(step_count as usize);
+		for _ in 0..step_count {
+			let level = reader.u32()?;
+			let gravity = reader.u32()?;
+			if gravity > MAX_GRAVITY {
+				return Err(ReplayError::InvalidValue("gravity"));
+			}
+			steps.push((level, gravity));
+		}
+		GravityTable::new(steps)
+	};
+
+	let lines_per_level = reader.u32()?;
+	let preview_len = reader.u8()? as usize;
+	let delay = reader.duration()?;
+	let reset = match reader.u8()? {
+		0 => LockReset::Step,
+		1 => LockReset::Move,
+		_ => return Err(ReplayError::InvalidValue("lock reset")),
+	};
+	let lock_delay = LockDelay {
+		delay,
+		reset,
+		max_resets: reader.u32()?,
+	};
+	if lock_delay.clamped() != lock_delay {
+		return Err(ReplayError::InvalidValue("lock delay"));
+	}
+	let all_spin = match reader.u8()? {
+		0 => false,
+		1 => true,
+		_ => return Err(ReplayError::InvalidValue("all spin flag")),
+	};
+
+	let handling = Handling {
+		das: reader.duration()?,
+		arr: reader.duration()?,
+		soft_drop_factor: reader.u32()?,
+		das_cut: reader.duration()?,
+	};
+	if handling.clamped() != handling {
+		return Err(ReplayError::InvalidValue("soft drop factor"));
+	}
+
+	let mode = match reader.u8()? {
+		0 => GameMode::Marathon,
+		1 => GameMode::Sprint {
+			lines: reader.u32()?,
+		},
+		_ => return Err(ReplayError::InvalidValue("mode")),
+	};
+
+	Ok(Rules {
+		mode,
+		rule_set,
+		dimensions,
+		randomizer,
+		gravity,
+		lines_per_level,
+		preview_len,
+		lock_delay,
+		all_spin,
+		handling,
+	})
+}
+
+#[derive(Default)]
+struct Writer {
+	bytes: Vec<u8>,
+}
+
+impl Writer {
+	fn bytes(&mut self, bytes: &[u8]) {
+		self.bytes.extend_from_slice(bytes);
+	}
+
+	fn u8(&mut self, value: u8) {
+		self.bytes.push(value);
+	}
+
+	fn u16(&mut self, value: u16) {
+		self.bytes(&value.to_le_bytes());
+	}
+
+	fn u32(&mut self, value: u32) {
+		self.bytes(&value.to_le_bytes());
+	}
+
+	fn u64(&mut self, value: u64) {
+		self.bytes(&value.to_le_bytes());
+	}
+
+	fn varint(&mut self, mut value: u64) {
+		while value >= 0x80 {
+			self.u8(value as u8 | 0x80);
+			value >>= 7;
+		}
+		self.u8(value as u8);
+	}
+
+	// to the millisecond, which is finer than a tick anyway
+	fn duration(&mut self, duration: Duration) {
+		self.u32(duration.as_millis().min(u32::MAX as u128) as u32);
+	}
+}
+
+struct Reader<'a> {
+	bytes: &'a [u8],
+	position: usize,
+}
+
+impl<'a> Reader<'a> {
+	fn new(bytes: &'a [u8]) -> Reader<'a> {
+		Reader { bytes, position: 0 }
+	}
+
+	fn remaining(&self) -> usize {
+		self.bytes.len() - self.position
+	}
+
+	fn take<const N: usize>(&mut self) -> Result<[u8; N], ReplayError> {
+		let end = self.position + N;
+		let bytes = self
+			.bytes
+			.get(self.position..end)
+			.ok_or(ReplayError::Truncated)?;
+		self.position = end;
+		let mut array = [0; N];
+		array.copy_from_slice(bytes);
+		Ok(array)
+	}
+
+	fn u8(&mut self) -> Result<u8, ReplayError> {
+		Ok(self.take::<1>()?[0])
+	}
+
+	fn u16(&mut self) -> Result<u16, ReplayError> {
+		Ok(u16::from_le_bytes(self.take()?))
+	}
+
+	fn u32(&mut self) -> Result<u32, ReplayError> {
+		Ok(u32::from_le_bytes(self.take()?))
+	}
+
+	fn u64(&mut self) -> Result<u64, ReplayError> {
+		Ok(u64::from_le_bytes(self.take()?))
+	}
+
+	fn varint(&mut self) -> Result<u64, ReplayError> {
+		let mut value = 0;
+		for shift in (0..64).step_by(7) {
+			let byte = self.u8()?;
+			value |= ((byte & 0x7f) as u64) << shift;
+			if byte & 0x80 == 0 {
+				return Ok(value);
+			}
+		}
+		Err(ReplayError::InvalidValue("varint"))
+	}
+
+	fn duration(&mut self) -> Result<Duration, ReplayError> {
+		Ok(Duration::from_millis(self.u32()? as u64))
+	}
+}
+
+fn fnv1a(bytes: &[u8]) -> u32 {
+	bytes.iter().fold(0x811c_9dc5, |hash, byte| {
+		(hash ^ *byte as u32).wrapping_mul(0x0100_0193)
+	})
+}
+
+fn base64_encode(bytes: &[u8]) -> String {
+	let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+	for chunk in bytes.chunks(3) {
+		let group = chunk.iter().enumerate().fold(0u32, |group, (index, byte)| {
+			group | (*byte as u32) << (16 - index * 8)
+		});
+		// three bytes make four characters, and anything short of that makes one more than it has
+		for index in 0..=chunk.len() {
+			let sextet = (group >> (18 - index * 6)) & 0x3f;
+			text.push(BASE64_ALPHABET[sextet as usize] as char);
+		}
+	}
+	text
+}
+
+fn base64_decode(text: &str) -> Result<Vec<u8>, ReplayError> {
+	// anyone else's padding is fine, we just don't need it
+	let text = text.trim_end_matches('=').as_bytes();
+	if text.len() % 4 == 1 {
+		return Err(ReplayError::InvalidText);
+	}
+
+	let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+	for chunk in text.chunks(4) {
+		let mut group = 0u32;
+		for (index, character) in chunk.iter().enumerate() {
+			let sextet = BASE64_ALPHABET
+				.iter()
+				.position(|other| other == character)
+				.ok_or(ReplayError::InvalidText)?;
+			group |= (sextet as u32) << (18 - index * 6);
+		}
+		for index in 0..chunk.len() - 1 {
+			bytes.push((group >> (16 - index * 8)) as u8);
+		}
+	}
+	Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::engine::Engine;
+	use crate::gravity::RuleSet;
+	use crate::input::Button;
+
+	// a short game with a bit of everything in it, so the runs aren't all the same
+	fn recorded_game(rules: Rules) -> Replay {
+		let mut engine = Engine::new(rules, 42);
+		let buttons = [
+			Button::Left,
+			Button::RotateClockwise,
+			Button::Right,
+			Button::Hold,
+			Button::HardDrop,
+		];
+		for tick in 0..3000 {
+			let mut input = Input::default();
+			if tick % 11 == 0 {
+				input.press(buttons[tick / 11 % buttons.len()]);
+			}
+			engine.tick(input);
+		}
+		engine.replay().unwrap().clone()
+	}
+
+	// puts a fresh checksum on the end of a replay that's been tampered with
+	fn with_checksum(mut body: Vec<u8>) -> Vec<u8> {
+		let checksum = fnv1a(&body);
+		body.extend_from_slice(&checksum.to_le_bytes());
+		body
+	}
+
+	#[test]
+	fn encode_round_trips() {
+		let replay = recorded_game(Rules::default());
+		assert_eq!(Replay::decode(&replay.encode()), Ok(replay));
+	}
+
+	#[test]
+	fn text_round_trips() {
+		let replay = recorded_game(Rules {
+			mode: GameMode::sprint(),
+			dimensions: Dimensions::new(8, 16, 3),
+			all_spin: true,
+			preview_len: 3,
+			..Rules::new(RuleSet::Nes)
+		});
+		let text = replay.to_text();
+		assert!(text
+			.bytes()
+			.all(|byte| byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_'));
+		assert_eq!(Replay::from_text(&text), Ok(replay));
+	}
+
+	#[test]
+	fn rejects_newer_formats() {
+		let mut bytes = recorded_game(Rules::default()).encode();
+		bytes[4..6].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+		assert_eq!(
+			Replay::decode(&bytes),
+			Err(ReplayError::UnsupportedFormat {
+				found: FORMAT_VERSION + 1,
+				supported: FORMAT_VERSION,
+			})
+		);
+	}
+
+	#[test]
+	fn rejects_other_engine_versions() {
+		let mut bytes = recorded_game(Rules::default()).encode();
+		bytes.truncate(bytes.len() - 4);
+		bytes[6..8].copy_from_slice(&(ENGINE_VERSION + 1).to_le_bytes());
+		assert_eq!(
+			Replay::decode(&with_checksum(bytes)),
+			Err(ReplayError::WrongEngineVersion {
+				found: ENGINE_VERSION + 1,
+				expected: ENGINE_VERSION,
+			})
+		);
+	}
+
+	// a replay of a game under these rules, with one of its u32 fields swapped for another value
+	fn with_field(rules: Rules, field: u32, value: u32) -> Vec<u8> {
+		let mut bytes = Replay::new(rules, 1).encode();
+		bytes.truncate(bytes.len() - 4);
+		let at = bytes
+			.windows(4)
+			.position(|window| window == field.to_le_bytes())
+			.unwrap();
+		bytes[at..at + 4].copy_from_slice(&value.to_le_bytes());
+		with_checksum(bytes)
+	}
+
+	#[test]
+	fn rejects_rules_out_of_range() {
+		let soft_drop = Rules {
+			handling: Handling {
+				soft_drop_factor: 0x0004_0302,
+				..Handling::default()
+			},
+			..Rules::default()
+		};
+		assert_eq!(
+			Replay::decode(&with_field(soft_drop, 0x0004_0302, u32::MAX)),
+			Err(ReplayError::InvalidValue("soft drop factor"))
+		);
+
+		let lock_resets = Rules {
+			lock_delay: LockDelay {
+				max_resets: 0x0302,
+				..LockDelay::default()
+			},
+			..Rules::default()
+		};
+		assert_eq!(
+			Replay::decode(&with_field(lock_resets, 0x0302, u32::MAX)),
+			Err(ReplayError::InvalidValue("lock delay"))
+		);
+	}
+
+	#[test]
+	fn rules_are_written_as_theyre_played() {
+		let rules = Rules {
+			handling: Handling {
+				soft_drop_factor: u32::MAX,
+				..Handling::default()
+			},
+			..Rules::default()
+		};
+		let decoded = Replay::decode(&Replay::new(rules.clone(), 1).encode()).unwrap();
+		assert_eq!(decoded.rules().handling, rules.handling.clamped());
+	}
+
+	#[test]
+	fn rejects_bad_checksums() {
+		let mut bytes = recorded_game(Rules::default()).encode();
+		let middle = bytes.len() / 2;
+		bytes[middle] ^= 0xff;
+		assert_eq!(Replay::decode(&bytes), Err(ReplayError::BadChecksum));
+	}
+
+	#[test]
+	fn rejects_truncated_replays() {
+		let bytes = recorded_game(Rules::default()).encode();
+		assert_eq!(Replay::decode(&bytes[..8]), Err(ReplayError::Truncated));
+
+		// cut off partway through the rules, but with the checksum patched up to match
+		let body = bytes[..20].to_vec();
+		assert_eq!(
+			Replay::decode(&with_checksum(body)),
+			Err(ReplayError::Truncated)
+		);
+	}
+
+	#[test]
+	fn rejects_absurd_tick_counts() {
+		// an empty replay ends in a tick count of 0, which we swap for one run of far too many
+		let mut body = Replay::new(Rules::default(), 1).encode();
+		body.truncate(body.len() - 5);
+		let mut writer = Writer { bytes: body };
+		writer.varint(1 << 31);
+		writer.varint(1 << 31);
+		writer.u8(0);
+		writer.u8(0);
+		assert_eq!(
+			Replay::decode(&with_checksum(writer.bytes)),
+			Err(ReplayError::InvalidValue("tick count"))
+		);
+	}
+
+	#[test]
+	fn rejects_other_files() {
+		assert_eq!(
+			Replay::decode(b"not a replay"),
+			Err(ReplayError::NotAReplay)
+		);
+		assert_eq!(Replay::from_text("!!!"), Err(ReplayError::InvalidText));
+	}
+}
@@ -0,0 +1,134 @@
+use crate::board::Dimensions;
+use crate::gravity::{GravityTable, RuleSet};
+use crate::handling::Handling;
+use crate::randomizer::RandomizerKind;
+use std::time::Duration;
+
+const DEFAULT_LINES_PER_LEVEL: u32 = 10;
+const DEFAULT_PREVIEW_LEN: usize = 5;
+pub const MAX_PREVIEW_LEN: usize = 6;
+// a minute sat on the stack, or a thousand last-second moves, is more than anyone needs
+pub const MAX_LOCK_DELAY: Duration = Duration::from_secs(60);
+pub const MAX_LOCK_RESETS: u32 = 1_000;
+pub const DEFAULT_SPRINT_LINES: u32 = 40;
+
+// what the player's going for, and so when the game ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameMode {
+	// keep going until topping out, for as many points as possible
+	#[default]
+	Marathon,
+	// clear this many lines as quickly as possible. Getting there wins
+	Sprint {
+		lines: u32,
+	},
+}
+
+impl GameMode {
+	pub fn sprint() -> GameMode {
+		GameMode::Sprint {
+			lines: DEFAULT_SPRINT_LINES,
+		}
+	}
+
+	// how many lines it takes to win, if there's any winning at all
+	pub fn line_goal(self) -> Option<u32> {
+		match self {
+			GameMode::Marathon => None,
+			GameMode::Sprint { lines } => Some(lines.max(1)),
+		}
+	}
+
+	pub fn name(self) -> &'static str {
+		match self {
+			GameMode::Marathon => "marathon",
+			GameMode::Sprint { .. } => "sprint",
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LockReset {
+	// only falling to a new lowest row restarts the lock timer
+	Step,
+	// any successful move or rotation restarts it, up to a limit
+	#[default]
+	Move,
+}
+
+// how long a piece can sit on the stack before it locks in place
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LockDelay {
+	pub delay: Duration,
+	pub reset: LockReset,
+	// how many times moving can restart the timer before the piece has to reach a new lowest row
+	pub max_resets: u32,
+}
+
+impl Default for LockDelay {
+	fn default() -> LockDelay {
+		LockDelay {
+			delay: Duration::from_millis(500),
+			reset: LockReset::default(),
+			max_resets: 15,
+		}
+	}
+}
+
+impl LockDelay {
+	pub fn clamped(self) -> LockDelay {
+		LockDelay {
+			delay: self.delay.min(MAX_LOCK_DELAY),
+			max_resets: self.max_resets.min(MAX_LOCK_RESETS),
+			..self
+		}
+	}
+}
+
+// everything that changes how a game plays out. Two engines with the same rules and seed will
+// play out exactly the same given the same inputs
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rules {
+	// saves from before there were modes were all marathons
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub mode: GameMode,
+	pub rule_set: RuleSet,
+	pub dimensions: Dimensions,
+	pub randomizer: RandomizerKind,
+	pub gravity: GravityTable,
+	pub lines_per_level: u32,
+	// how many upcoming pieces the player gets to see, from 1 to MAX_PREVIEW_LEN
+	pub preview_len: usize,
+	pub lock_delay: LockDelay,
+	// whether pieces other than T can spin, scored like T-spin minis
+	pub all_spin: bool,
+	pub handling: Handling,
+}
+
+impl Default for Rules {
+	fn default() -> Rules {
+		Rules::new(RuleSet::default())
+	}
+}
+
+impl Rules {
+	// the defaults for a given rule set. Any of these can be tweaked afterwards
+	pub fn new(rule_set: RuleSet) -> Rules {
+		Rules {
+			mode: GameMode::default(),
+			rule_set,
+			dimensions: Dimensions::default(),
+			randomizer: RandomizerKind::default(),
+			gravity: rule_set.gravity_table(),
+			lines_per_level: DEFAULT_LINES_PER_LEVEL,
+			preview_len: DEFAULT_PREVIEW_LEN,
+			lock_delay: LockDelay::default(),
+			all_spin: false,
+			handling: Handling::default(),
+		}
+	}
+}
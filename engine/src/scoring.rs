@@ -0,0 +1,305 @@
+// Guideline scoring: https://tetris.wiki/Scoring#Recent_guideline_compatible_games
+use crate::spin::{Spin, SpinKind};
+
+const POINTS_PER_SOFT_DROP_CELL: u32 = 1;
+const POINTS_PER_HARD_DROP_CELL: u32 = 2;
+const POINTS_PER_COMBO: u32 = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClearKind {
+	Single,
+	Double,
+	Triple,
+	Tetris,
+}
+
+impl ClearKind {
+	pub fn from_lines(lines: u32) -> Option<ClearKind> {
+		match lines {
+			0 => None,
+			1 => Some(ClearKind::Single),
+			2 => Some(ClearKind::Double),
+			3 => Some(ClearKind::Triple),
+			_ => Some(ClearKind::Tetris),
+		}
+	}
+
+	pub fn lines(self) -> u32 {
+		match self {
+			ClearKind::Single => 1,
+			ClearKind::Double => 2,
+			ClearKind::Triple => 3,
+			ClearKind::Tetris => 4,
+		}
+	}
+
+	fn perfect_clear_points(self) -> u32 {
+		match self {
+			ClearKind::Single => 800,
+			ClearKind::Double => 1200,
+			ClearKind::Triple => 1800,
+			ClearKind::Tetris => 2000,
+		}
+	}
+}
+
+// what clearing these lines is worth before the level, back-to-back and combo bonuses
+fn action_points(kind: Option<ClearKind>, spin: Option<SpinKind>) -> u32 {
+	match (spin, kind) {
+		(None, None) => 0,
+		(None, Some(ClearKind::Single)) => 100,
+		(None, Some(ClearKind::Double)) => 300,
+		(None, Some(ClearKind::Triple)) => 500,
+		(None, Some(ClearKind::Tetris)) => 800,
+
+		(Some(SpinKind::Mini), None) => 100,
+		(Some(SpinKind::Mini), Some(ClearKind::Single)) => 200,
+		(Some(SpinKind::Mini), Some(_)) => 400,
+
+		(Some(SpinKind::Full), None) => 400,
+		(Some(SpinKind::Full), Some(ClearKind::Single)) => 800,
+		(Some(SpinKind::Full), Some(ClearKind::Double)) => 1200,
+		(Some(SpinKind::Full), Some(_)) => 1600,
+	}
+}
+
+// what a single lock was worth. Spins count even if they don't clear anything
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LineClear {
+	pub kind: Option<ClearKind>,
+	pub spin: Option<Spin>,
+	pub points: u32,
+	// how many clears in a row this makes, not counting the first
+	pub combo: u32,
+	pub back_to_back: bool,
+	pub perfect_clear: bool,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Scoring {
+	score: u32,
+	lines: u32,
+	lines_per_level: u32,
+	level: u32,
+	// None until something's been cleared, then counts up for every lock that clears a line
+	combo: Option<u32>,
+	back_to_back: bool,
+}
+
+impl Scoring {
+	pub fn new(lines_per_level: u32) -> Scoring {
+		Scoring {
+			score: 0,
+			lines: 0,
+			lines_per_level: lines_per_level.max(1),
+			level: 1,
+			combo: None,
+			back_to_back: false,
+		}
+	}
+
+	pub fn score(&self) -> u32 {
+		self.score
+	}
+
+	pub fn lines(&self) -> u32 {
+		self.lines
+	}
+
+	pub fn level(&self) -> u32 {
+		self.level
+	}
+
+	pub fn combo(&self) -> Option<u32> {
+		self.combo
+	}
+
+	// whether the next difficult clear gets the back-to-back bonus
+	pub fn is_back_to_back(&self) -> bool {
+		self.back_to_back
+	}
+
+	pub(crate) fn soft_drop(&mut self, cells: u32) {
+		self.score += cells * POINTS_PER_SOFT_DROP_CELL;
+	}
+
+	pub(crate) fn hard_drop(&mut self, cells: u32) {
+		self.score += cells * POINTS_PER_HARD_DROP_CELL;
+	}
+
+	// called whenever a piece locks, with however many lines it completed
+	pub(crate) fn lock(
+		&mut self,
+		lines: u32,
+		spin: Option<Spin>,
+		perfect_clear: bool,
+	) -> Option<LineClear> {
+		let kind = ClearKind::from_lines(lines);
+		if kind.is_none() && spin.is_none() {
+			self.combo = None;
+			return None;
+		}
+
+		let spin_kind = spin.map(|spin| spin.kind);
+		let mut points = action_points(kind, spin_kind) * self.level;
+		let mut combo = 0;
+		let mut back_to_back = false;
+
+		if let Some(kind) = kind {
+			combo = self.combo.map_or(0, |combo| combo + 1);
+			self.combo = Some(combo);
+
+			// tetrises and spins keep a back-to-back chain going, anything else breaks it
+			let is_difficult = kind == ClearKind::Tetris || spin.is_some();
+			back_to_back = is_difficult && self.back_to_back;
+			self.back_to_back = is_difficult;
+
+			if back_to_back {
+				points = points * 3 / 2;
+			}
+			points += POINTS_PER_COMBO * combo * self.level;
+			if perfect_clear {
+				points += kind.perfect_clear_points() * self.level;
+			}
+		} else {
+			// a spin that didn't clear anything doesn't keep a combo going
+			self.combo = None;
+		}
+
+		self.score += points;
+
+		// the clear is scored at the level it was made on, and only then do we level up
+		self.lines += lines;
+		self.level = 1 + self.lines / self.lines_per_level;
+
+		Some(LineClear {
+			kind,
+			spin,
+			points,
+			combo,
+			back_to_back,
+			perfect_clear,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::piece::PieceKind;
+
+	#[test]
+	fn clears_score_by_size() {
+		let mut scoring = Scoring::new(10);
+		let points: Vec<u32> = (1..=4)
+			.map(|lines| {
+				let clear = scoring.lock(lines, None, false).unwrap();
+				// a gap between each so none of them count as a combo
+				assert_eq!(scoring.lock(0, None, false), None);
+				clear.points
+			})
+			.collect();
+		assert_eq!(points, [100, 300, 500, 800]);
+		assert_eq!(scoring.score(), 1700);
+	}
+
+	#[test]
+	fn tetrises_in_a_row_are_back_to_back() {
+		let mut scoring = Scoring::new(10);
+		let first = scoring.lock(4, None, false).unwrap();
+		assert!(!first.back_to_back);
+		assert!(scoring.is_back_to_back());
+
+		// a lock that clears nothing doesn't break the chain
+		scoring.lock(0, None, false);
+		let second = scoring.lock(4, None, false).unwrap();
+		assert!(second.back_to_back);
+		assert_eq!(second.points, 1200);
+
+		// but an easier clear does
+		scoring.lock(0, None, false);
+		scoring.lock(1, None, false);
+		scoring.lock(0, None, false);
+		assert!(!scoring.lock(4, None, false).unwrap().back_to_back);
+	}
+
+	#[test]
+	fn combos_count_clears_in_a_row() {
+		let mut scoring = Scoring::new(10);
+		let combos: Vec<(u32, u32)> = (0..3)
+			.map(|_| {
+				let clear = scoring.lock(1, None, false).unwrap();
+				(clear.combo, clear.points)
+			})
+			.collect();
+		assert_eq!(combos, [(0, 100), (1, 150), (2, 200)]);
+
+		assert_eq!(scoring.lock(0, None, false), None);
+		assert_eq!(scoring.combo(), None);
+		assert_eq!(scoring.lock(1, None, false).unwrap().combo, 0);
+	}
+
+	#[test]
+	fn perfect_clears_add_a_bonus() {
+		let mut scoring = Scoring::new(10);
+		let clear = scoring.lock(2, None, true).unwrap();
+		assert!(clear.perfect_clear);
+		assert_eq!(clear.points, 300 + 1200);
+	}
+
+	#[test]
+	fn spins_score_even_without_a_clear() {
+		let t_spin = |kind| Spin {
+			piece: PieceKind::T,
+			kind,
+		};
+		let mut scoring = Scoring::new(10);
+		let spin = scoring
+			.lock(0, Some(t_spin(SpinKind::Full)), false)
+			.unwrap();
+		assert_eq!(spin.kind, None);
+		assert_eq!(spin.points, 400);
+		assert_eq!(scoring.combo(), None);
+
+		assert_eq!(
+			scoring
+				.lock(2, Some(t_spin(SpinKind::Full)), false)
+				.unwrap()
+				.points,
+			1200
+		);
+		// a spin keeps the back-to-back chain going just like a tetris
+		let mini = scoring
+			.lock(1, Some(t_spin(SpinKind::Mini)), false)
+			.unwrap();
+		assert!(mini.back_to_back);
+		assert_eq!(mini.points, 300 + 50);
+	}
+
+	#[test]
+	fn levels_go_up_every_so_many_lines() {
+		let mut scoring = Scoring::new(4);
+		scoring.lock(3, None, false);
+		assert_eq!(scoring.level(), 1);
+
+		// the clear that crosses the line is still scored at the old level
+		let clear = scoring.lock(2, None, false).unwrap();
+		assert_eq!(clear.points, 300 + 50);
+		assert_eq!(scoring.level(), 2);
+		assert_eq!(scoring.lines(), 5);
+
+		scoring.lock(0, None, false);
+		assert_eq!(scoring.lock(1, None, false).unwrap().points, 200);
+	}
+
+	#[test]
+	fn drops_score_per_cell() {
+		let mut scoring = Scoring::new(10);
+		scoring.soft_drop(3);
+		scoring.hard_drop(10);
+		assert_eq!(scoring.score(), 3 + 20);
+	}
+}
@@ -0,0 +1,156 @@
+// Spotting T-spins (and, optionally, spins with every other piece) when a piece locks.
+// https://tetris.wiki/T-Spin
+use crate::board::Board;
+use crate::piece::{Piece, PieceKind};
+use crate::srs::{Kick, Rotation};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SpinKind {
+	Mini,
+	Full,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Spin {
+	pub piece: PieceKind,
+	pub kind: SpinKind,
+}
+
+// only called when the last thing the piece did before locking was rotate
+pub(crate) fn detect(
+	piece: &Piece,
+	board: &Board,
+	kick: Option<Kick>,
+	all_spin: bool,
+) -> Option<Spin> {
+	match piece.kind {
+		PieceKind::T => detect_t_spin(piece, board, kick),
+		PieceKind::O => None,
+		_ if all_spin && is_immobile(piece, board) => Some(Spin {
+			piece: piece.kind,
+			kind: SpinKind::Mini,
+		}),
+		_ => None,
+	}
+}
+
+// the three-corner rule: at least three of the corners around the T's center have to be filled,
+// and it's only a full T-spin if both of the ones it's pointing at are
+fn detect_t_spin(piece: &Piece, board: &Board, kick: Option<Kick>) -> Option<Spin> {
+	let x = piece.top_left.x;
+	let y = piece.top_left.y;
+	let is_filled = |(dx, dy): (i32, i32)| board.is_occupied(x + dx, y + dy);
+
+	let top_left = is_filled((0, 0));
+	let top_right = is_filled((2, 0));
+	let bottom_left = is_filled((0, 2));
+	let bottom_right = is_filled((2, 2));
+
+	let corners = [top_left, top_right, bottom_left, bottom_right]
+		.iter()
+		.filter(|filled| **filled)
+		.count();
+	if corners < 3 {
+		return None;
+	}
+
+	let front = match piece.rotation {
+		Rotation::Spawn => top_left && top_right,
+		Rotation::Right => top_right && bottom_right,
+		Rotation::Reverse => bottom_left && bottom_right,
+		Rotation::Left => top_left && bottom_left,
+	};
+	// the one-across, two-down kick is what squeezes a T into a T-spin triple slot
+	let is_tst_kick = kick.is_some_and(|kick| kick.offset.x.abs() == 1 && kick.offset.y.abs() == 2);
+
+	Some(Spin {
+		piece: PieceKind::T,
+		kind: if front || is_tst_kick {
+			SpinKind::Full
+		} else {
+			SpinKind::Mini
+		},
+	})
+}
+
+// stuck in place: can't go left, right or up
+fn is_immobile(piece: &Piece, board: &Board) -> bool {
+	[(-1, 0), (1, 0), (0, -1)].iter().all(|(dx, dy)| {
+		piece
+			.cells()
+			.any(|position| board.is_occupied(position.x + dx, position.y + dy))
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::piece::Vector2D;
+
+	// a T pointing down, with its bounding box's top left at (3, 20)
+	fn upside_down_t() -> Piece {
+		let mut piece = Piece::new(PieceKind::T);
+		piece.rotate_clockwise();
+		piece.rotate_clockwise();
+		piece.rotation = Rotation::Reverse;
+		piece.top_left = Vector2D { x: 3, y: 20 };
+		piece
+	}
+
+	// fills in the given corners of the T's bounding box
+	fn board_with_corners(corners: &[(i32, i32)]) -> Board {
+		let mut board = Board::default();
+		for (x, y) in corners {
+			board.set(3 + x, 20 + y, PieceKind::O);
+		}
+		board
+	}
+
+	#[test]
+	fn both_front_corners_make_a_full_t_spin() {
+		let board = board_with_corners(&[(0, 2), (2, 2), (0, 0)]);
+		assert_eq!(
+			detect(&upside_down_t(), &board, None, false),
+			Some(Spin {
+				piece: PieceKind::T,
+				kind: SpinKind::Full,
+			})
+		);
+	}
+
+	#[test]
+	fn one_front_corner_makes_a_mini() {
+		let board = board_with_corners(&[(0, 0), (2, 0), (0, 2)]);
+		assert_eq!(
+			detect(&upside_down_t(), &board, None, false),
+			Some(Spin {
+				piece: PieceKind::T,
+				kind: SpinKind::Mini,
+			})
+		);
+	}
+
+	#[test]
+	fn the_t_spin_triple_kick_upgrades_a_mini() {
+		let board = board_with_corners(&[(0, 0), (2, 0), (0, 2)]);
+		let kick = Kick {
+			index: 4,
+			offset: Vector2D { x: -1, y: 2 },
+		};
+		assert_eq!(
+			detect(&upside_down_t(), &board, Some(kick), false),
+			Some(Spin {
+				piece: PieceKind::T,
+				kind: SpinKind::Full,
+			})
+		);
+	}
+
+	#[test]
+	fn two_corners_arent_enough() {
+		let board = board_with_corners(&[(0, 2), (2, 2)]);
+		assert_eq!(detect(&upside_down_t(), &board, None, false), None);
+	}
+}
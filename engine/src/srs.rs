@@ -0,0 +1,244 @@
+// The Super Rotation System: every piece rotates within its bounding box, and if that
+// collides we try a handful of nearby offsets ("kicks") before giving up.
+// https://tetris.wiki/Super_Rotation_System
+use crate::board::Board;
+use crate::piece::{Piece, PieceKind, Vector2D};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Rotation {
+	// as the piece spawns
+	#[default]
+	Spawn,
+	// one turn clockwise from spawn
+	Right,
+	// upside down
+	Reverse,
+	// one turn counter-clockwise from spawn
+	Left,
+}
+
+impl Rotation {
+	fn index(self) -> usize {
+		match self {
+			Rotation::Spawn => 0,
+			Rotation::Right => 1,
+			Rotation::Reverse => 2,
+			Rotation::Left => 3,
+		}
+	}
+
+	fn from_index(index: usize) -> Rotation {
+		match index % 4 {
+			0 => Rotation::Spawn,
+			1 => Rotation::Right,
+			2 => Rotation::Reverse,
+			_ => Rotation::Left,
+		}
+	}
+
+	pub fn clockwise(self) -> Rotation {
+		Rotation::from_index(self.index() + 1)
+	}
+
+	pub fn counter_clockwise(self) -> Rotation {
+		Rotation::from_index(self.index() + 3)
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RotationDirection {
+	Clockwise,
+	CounterClockwise,
+	// straight to upside down, without having to fit through the quarter turn in between
+	Half,
+}
+
+// which of the kick tests let a rotation through. Index 0 is the plain rotation without any kick
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Kick {
+	pub index: usize,
+	pub offset: Vector2D,
+}
+
+// NOTE: these are written y-up to match every reference table out there, so we flip y when
+// applying them
+type KickTable = [[(i32, i32); 5]; 8];
+
+const JLSTZ_KICKS: KickTable = [
+	[(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)], // 0 -> R
+	[(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],     // R -> 0
+	[(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],     // R -> 2
+	[(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)], // 2 -> R
+	[(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],    // 2 -> L
+	[(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],  // L -> 2
+	[(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],  // L -> 0
+	[(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],    // 0 -> L
+];
+
+const I_KICKS: KickTable = [
+	[(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)], // 0 -> R
+	[(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)], // R -> 0
+	[(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)], // R -> 2
+	[(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)], // 2 -> R
+	[(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)], // 2 -> L
+	[(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)], // L -> 2
+	[(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)], // L -> 0
+	[(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)], // 0 -> L
+];
+
+// SRS doesn't cover half turns, so these are the ones most modern games have settled on. Every
+// piece uses the same ones
+type HalfTurnKickTable = [[(i32, i32); 6]; 4];
+
+const HALF_TURN_KICKS: HalfTurnKickTable = [
+	[(0, 0), (0, 1), (1, 1), (-1, 1), (1, 0), (-1, 0)], // 0 -> 2
+	[(0, 0), (1, 0), (1, 2), (1, 1), (0, 2), (0, 1)],   // R -> L
+	[(0, 0), (0, -1), (-1, -1), (1, -1), (-1, 0), (1, 0)], // 2 -> 0
+	[(0, 0), (-1, 0), (-1, 2), (-1, 1), (0, 2), (0, 1)], // L -> R
+];
+
+fn kick_row(from: Rotation, to: Rotation) -> usize {
+	match (from, to) {
+		(Rotation::Spawn, Rotation::Right) => 0,
+		(Rotation::Right, Rotation::Spawn) => 1,
+		(Rotation::Right, Rotation::Reverse) => 2,
+		(Rotation::Reverse, Rotation::Right) => 3,
+		(Rotation::Reverse, Rotation::Left) => 4,
+		(Rotation::Left, Rotation::Reverse) => 5,
+		(Rotation::Left, Rotation::Spawn) => 6,
+		(Rotation::Spawn, Rotation::Left) => 7,
+		_ => unreachable!("SRS only kicks quarter turns"),
+	}
+}
+
+// rotates the piece in place if any of the kick tests fit, reporting which one did
+pub(crate) fn rotate(
+	piece: &mut Piece,
+	direction: RotationDirection,
+	board: &Board,
+) -> Option<Kick> {
+	// the O piece looks the same every way round, so it shouldn't budge
+	if piece.kind == PieceKind::O {
+		return None;
+	}
+
+	let mut rotated = piece.clone();
+	let to = match direction {
+		RotationDirection::Clockwise => {
+			rotated.rotate_clockwise();
+			piece.rotation.clockwise()
+		}
+		RotationDirection::CounterClockwise => {
+			rotated.rotate_counter_clockwise();
+			piece.rotation.counter_clockwise()
+		}
+		RotationDirection::Half => {
+			rotated.rotate_clockwise();
+			rotated.rotate_clockwise();
+			piece.rotation.clockwise().clockwise()
+		}
+	};
+	rotated.rotation = to;
+
+	let kicks: &[(i32, i32)] = if direction == RotationDirection::Half {
+		&HALF_TURN_KICKS[piece.rotation.index()]
+	} else if piece.kind == PieceKind::I {
+		&I_KICKS[kick_row(piece.rotation, to)]
+	} else {
+		&JLSTZ_KICKS[kick_row(piece.rotation, to)]
+	};
+
+	for (index, (x, y)) in kicks.iter().enumerate() {
+		let offset = Vector2D { x: *x, y: -*y };
+		let mut candidate = rotated.clone();
+		candidate.top_left.x += offset.x;
+		candidate.top_left.y += offset.y;
+		if !candidate
+			.cells()
+			.any(|position| board.is_occupied(position.x, position.y))
+		{
+			*piece = candidate;
+			return Some(Kick { index, offset });
+		}
+	}
+
+	None
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn t_piece_at(x: i32, y: i32) -> Piece {
+		let mut piece = Piece::new(PieceKind::T);
+		piece.top_left = Vector2D { x, y };
+		piece
+	}
+
+	#[test]
+	fn rotates_in_place_when_theres_room() {
+		let board = Board::default();
+		let mut piece = t_piece_at(4, 10);
+		let kick = rotate(&mut piece, RotationDirection::Clockwise, &board);
+		assert_eq!(
+			kick,
+			Some(Kick {
+				index: 0,
+				offset: Vector2D { x: 0, y: 0 },
+			})
+		);
+		assert_eq!(piece.rotation, Rotation::Right);
+		assert_eq!(piece.top_left, Vector2D { x: 4, y: 10 });
+	}
+
+	#[test]
+	fn takes_the_first_kick_that_fits() {
+		let mut board = Board::default();
+		// right where the rotated T's bottom would go, so the plain rotation doesn't fit but the
+		// first kick (one to the left) does
+		board.set(5, 12, PieceKind::O);
+		let mut piece = t_piece_at(4, 10);
+		let kick = rotate(&mut piece, RotationDirection::Clockwise, &board);
+		assert_eq!(
+			kick,
+			Some(Kick {
+				index: 1,
+				offset: Vector2D { x: -1, y: 0 },
+			})
+		);
+		assert_eq!(piece.top_left, Vector2D { x: 3, y: 10 });
+	}
+
+	#[test]
+	fn stays_put_when_nothing_fits() {
+		let mut board = Board::default();
+		for x in 0..board.width() {
+			for y in 0..board.height() {
+				board.set(x, y, PieceKind::O);
+			}
+		}
+		let mut piece = t_piece_at(4, 10);
+		let before = piece.clone();
+		assert_eq!(
+			rotate(&mut piece, RotationDirection::Clockwise, &board),
+			None
+		);
+		assert_eq!(piece.rotation, Rotation::Spawn);
+		assert_eq!(piece.top_left, before.top_left);
+		assert_eq!(piece.squares, before.squares);
+	}
+
+	#[test]
+	fn o_pieces_never_move() {
+		let board = Board::default();
+		let mut piece = Piece::new(PieceKind::O);
+		piece.top_left = Vector2D { x: 4, y: 10 };
+		assert_eq!(
+			rotate(&mut piece, RotationDirection::Clockwise, &board),
+			None
+		);
+		assert_eq!(piece.rotation, Rotation::Spawn);
+	}
+}
@@ -0,0 +1,109 @@
+use super::inner::Inner;
+use rusty_tetris_engine::Event;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen(typescript_custom_section)]
+const GAME_EVENT_TS: &str = r#"
+export type PieceKind = "I" | "O" | "T" | "S" | "Z" | "J" | "L";
+
+export type GameEvent =
+	| { type: "piece_spawned"; piece: PieceKind }
+	| { type: "piece_locked"; piece: PieceKind }
+	/** sent as soon as the rows fill, before they're cleared away */
+	| { type: "lines_cleared"; count: number; kind: "Single" | "Double" | "Triple" | "Tetris" }
+	/** T-spins, plus spins with other pieces under rule sets that count them */
+	| { type: "spin"; piece: PieceKind; kind: "Mini" | "Full" }
+	| { type: "combo"; combo: number }
+	| { type: "level_up"; level: number }
+	/** the piece that's just gone into hold */
+	| { type: "hold"; piece: PieceKind }
+	| { type: "game_over"; score: number; lines: number; ticks: number }
+	| { type: "paused" }
+	| { type: "resumed" };
+
+export type GameEventName = GameEvent["type"];
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+	#[wasm_bindgen(typescript_type = "GameEventName")]
+	pub type EventName;
+
+	#[wasm_bindgen(typescript_type = "(event: GameEvent) => void")]
+	pub type EventCallback;
+}
+
+// what the page wants to hear about, by event name
+#[derive(Default)]
+pub struct Callbacks {
+	callbacks: HashMap<&'static str, Vec<js_sys::Function>>,
+}
+
+impl Callbacks {
+	pub fn add(&mut self, name: &EventName, callback: js_sys::Function) -> Result<(), JsValue> {
+		let name = event_name(name)?;
+		self.callbacks.entry(name).or_default().push(callback);
+		Ok(())
+	}
+
+	pub fn remove(&mut self, name: &EventName, callback: &js_sys::Function) -> Result<(), JsValue> {
+		let name = event_name(name)?;
+		let callback: &JsValue = callback;
+		if let Some(callbacks) = self.callbacks.get_mut(name) {
+			callbacks.retain(|existing| {
+				let existing: &JsValue = existing;
+				existing != callback
+			});
+		}
+		Ok(())
+	}
+
+	pub fn clear(&mut self) {
+		self.callbacks.clear();
+	}
+
+	fn get(&self, name: &str) -> Vec<js_sys::Function> {
+		self.callbacks.get(name).cloned().unwrap_or_default()
+	}
+}
+
+fn event_name(name: &EventName) -> Result<&'static str, JsValue> {
+	let name = name.as_string().unwrap_or_default();
+	Event::NAMES
+		.iter()
+		.copied()
+		.find(|known| *known == name)
+		.ok_or_else(|| JsValue::from(format!("unknown event {}", name)))
+}
+
+// hands whatever the engine's said since last time to the page. Neither the game nor the
+// callbacks are borrowed while a callback runs, so callbacks are free to pause, reset or even
+// destroy the game
+pub fn dispatch(inner: &RefCell<Inner>, callbacks: &RefCell<Callbacks>) {
+	let events = inner.borrow_mut().take_events();
+	for event in events {
+		let callbacks = callbacks.borrow().get(event.name());
+		if callbacks.is_empty() {
+			continue;
+		}
+
+		let payload = match serde_json::to_string(&event)
+			.map_err(|err| JsValue::from(err.to_string()))
+			.and_then(|json| js_sys::JSON::parse(&json))
+		{
+			Ok(payload) => payload,
+			Err(err) => {
+				log::warn!("couldn't send {} event: {:?}", event.name(), err);
+				continue;
+			}
+		};
+		// one callback throwing shouldn't stop the others, or the game
+		for callback in callbacks {
+			if let Err(err) = callback.call1(&JsValue::NULL, &payload) {
+				log::warn!("{} callback threw: {:?}", event.name(), err);
+			}
+		}
+	}
+}
@@ -1,121 +1,518 @@
-use super::inner::{self, Inner};
-use std::cell::RefCell;
-use std::rc::Rc;
+use super::events::{self, Callbacks, EventCallback, EventName};
+use super::handling::{HandlingOptions, HandlingSettings};
+use super::inner::Inner;
+use super::input::{Action, Bindings};
+use super::options::{GameOptions, Options};
+use super::scores;
+use rusty_tetris_engine::Replay;
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
 use wasm_bindgen::{prelude::*, JsCast, JsValue};
-use web_sys::{CanvasRenderingContext2d, Document};
+use web_sys::CanvasRenderingContext2d;
 
-pub const NUM_COLS: i32 = 10;
-pub const NUM_ROWS: i32 = 30;
-const BLOCK_SIZE: f64 = 25.;
+type FrameCallback = Closure<dyn FnMut(f64)>;
 
+#[wasm_bindgen]
+extern "C" {
+	#[wasm_bindgen(typescript_type = "HTMLCanvasElement | string")]
+	pub type CanvasTarget;
+}
+
+// One game on one canvas. It doesn't do anything until it's started, and keeps going until it's
+// destroyed
+#[wasm_bindgen]
 pub struct Game {
 	inner: Rc<RefCell<Inner>>,
+	// the animation frame callback, for as long as we're running. It only holds on to itself
+	// weakly, so dropping it here is all it takes to stop
+	frame_loop: Rc<RefCell<Option<FrameCallback>>>,
+	// the frame it's waiting on, so that can be called off too
+	frame_id: Rc<Cell<Option<i32>>>,
+	// everything we're listening to, until we're destroyed
+	listeners: RefCell<Vec<Listener>>,
+	is_destroyed: Cell<bool>,
+	// what the page's asked to hear about, via on()
+	callbacks: Rc<RefCell<Callbacks>>,
 }
 
+#[wasm_bindgen]
 impl Game {
-	// creates and initializes a new game. This might fail, so I'm avoiding the "new" convention
-	pub fn create() -> Result<Game, JsValue> {
-		let document = web_sys::window().unwrap().document().unwrap();
-		let width = 10. * BLOCK_SIZE;
-		let height = 30. * BLOCK_SIZE;
-		let (canvas, context) = create_canvas(&document, width, height)?;
+	// `new Game(canvasOrSelector, options)` from JS. Throws if there's no such canvas, it can't be
+	// drawn on, or the options don't make sense
+	#[wasm_bindgen(constructor)]
+	pub fn create(target: CanvasTarget, options: Option<GameOptions>) -> Result<Game, JsValue> {
+		let options = Options::from_js(options)?;
+		let (canvas, context) = create_canvas(&target)?;
 
 		let game = Game {
 			inner: Rc::new(RefCell::new(Inner::new(
-				width, height, BLOCK_SIZE, canvas, context,
+				options.rules()?,
+				options.handling,
+				options.block_size(),
+				options.seed(),
+				options.id.clone(),
+				canvas,
+				context,
 			))),
+			frame_loop: Rc::new(RefCell::new(None)),
+			frame_id: Rc::new(Cell::new(None)),
+			listeners: RefCell::new(Vec::new()),
+			is_destroyed: Cell::new(false),
+			callbacks: Rc::new(RefCell::new(Callbacks::default())),
 		};
 
-		Game::add_handlers(&game)?;
+		Game::add_handlers(&game, options.global_keys)?;
 		game.inner.clone().borrow_mut().focus()?;
 
 		Ok(game)
 	}
 
+	// starts the game loop going. Does nothing if it's already running
 	pub fn start(&self) -> Result<(), JsValue> {
-		let window = web_sys::window().unwrap();
+		if self.is_destroyed.get() {
+			return Err(JsValue::from("this game has been destroyed"));
+		}
+		if self.frame_loop.borrow().is_some() {
+			return Ok(());
+		}
+		self.inner.borrow_mut().restart_clock();
+
 		let game = self.inner.clone();
-		let closure = Closure::wrap(Box::new(move || {
+		let callbacks = self.callbacks.clone();
+		let frame_loop = Rc::downgrade(&self.frame_loop);
+		let frame_id = self.frame_id.clone();
+		*self.frame_loop.borrow_mut() = Some(Closure::wrap(Box::new(move |now: f64| {
+			frame_id.set(None);
 			game.borrow_mut()
-				.tick()
-				.expect("Something's gone wrong with tick");
-		}) as Box<dyn FnMut()>);
-		window.set_interval_with_callback_and_timeout_and_arguments_0(
-			closure.as_ref().unchecked_ref(),
-			inner::FPS,
-		)?;
-		closure.forget();
+				.frame(now)
+				.expect("Something's gone wrong with frame");
+			// a callback might have destroyed us, in which case there's no loop left to schedule
+			events::dispatch(&game, &callbacks);
+			schedule_frame(&frame_loop, &frame_id)
+				.expect("Something's gone wrong with requestAnimationFrame");
+		}) as Box<dyn FnMut(f64)>));
+
+		schedule_frame(&Rc::downgrade(&self.frame_loop), &self.frame_id)
+	}
+
+	pub fn pause(&self) {
+		self.inner.borrow_mut().set_paused(true);
+		self.dispatch();
+	}
+
+	pub fn resume(&self) {
+		self.inner.borrow_mut().set_paused(false);
+		self.dispatch();
+	}
+
+	// throws away the current game and starts a new one with the same options
+	pub fn reset(&self) {
+		self.inner.borrow_mut().reset();
+		self.dispatch();
+	}
+
+	// calls back with a GameEvent whenever the named thing happens. Callbacks run between frames,
+	// so they can safely call back into the game
+	pub fn on(&self, event: EventName, callback: EventCallback) -> Result<(), JsValue> {
+		self.callbacks
+			.borrow_mut()
+			.add(&event, callback.unchecked_into())
+	}
+
+	// stops calling a callback passed to on()
+	pub fn off(&self, event: EventName, callback: EventCallback) -> Result<(), JsValue> {
+		self.callbacks
+			.borrow_mut()
+			.remove(&event, callback.unchecked_ref())
+	}
+
+	// stops the game loop and stops listening for keys and focus, for good. The game is saved
+	// first, and the canvas is left showing whatever was last drawn
+	pub fn destroy(&self) {
+		if self.is_destroyed.replace(true) {
+			return;
+		}
+		self.inner.borrow_mut().set_paused(true);
+		self.dispatch();
+		self.stop();
+		self.listeners.borrow_mut().clear();
+		self.callbacks.borrow_mut().clear();
+	}
+
+	#[wasm_bindgen(getter)]
+	pub fn is_running(&self) -> bool {
+		self.frame_loop.borrow().is_some()
+	}
+
+	#[wasm_bindgen(getter)]
+	pub fn is_paused(&self) -> bool {
+		self.inner.borrow().is_paused()
+	}
+
+	#[wasm_bindgen(getter)]
+	pub fn is_game_over(&self) -> bool {
+		self.inner.borrow().engine().is_game_over()
+	}
+
+	#[wasm_bindgen(getter)]
+	pub fn did_win(&self) -> bool {
+		self.inner.borrow().engine().did_win()
+	}
+
+	#[wasm_bindgen(getter)]
+	pub fn score(&self) -> u32 {
+		self.inner.borrow().engine().score()
+	}
+
+	#[wasm_bindgen(getter)]
+	pub fn lines(&self) -> u32 {
+		self.inner.borrow().engine().lines()
+	}
+
+	#[wasm_bindgen(getter)]
+	pub fn level(&self) -> u32 {
+		self.inner.borrow().engine().level()
+	}
+
+	// how long the game's been going, in ticks of 1/60th of a second
+	#[wasm_bindgen(getter)]
+	pub fn ticks(&self) -> f64 {
+		self.inner.borrow().engine().ticks() as f64
+	}
+
+	// the upcoming pieces, soonest first, as letters like "T"
+	#[wasm_bindgen(getter)]
+	pub fn queue(&self) -> Vec<String> {
+		self.inner
+			.borrow()
+			.engine()
+			.queue()
+			.iter()
+			.map(|kind| kind.to_string())
+			.collect()
+	}
+
+	#[wasm_bindgen(getter)]
+	pub fn held_piece(&self) -> Option<String> {
+		self.inner
+			.borrow()
+			.engine()
+			.held_piece()
+			.map(|kind| kind.to_string())
+	}
+
+	// the current key bindings as JSON, mapping each action to the KeyboardEvent.codes that
+	// trigger it, like {"move_left": ["ArrowLeft"], ...}
+	pub fn bindings(&self) -> String {
+		self.inner.borrow().bindings().to_json()
+	}
+
+	// binds a KeyboardEvent.code (like "KeyZ") to an action (like "rotate_counter_clockwise").
+	// Bindings are saved as soon as they change, so they stick around between visits
+	pub fn bind_key(&self, action: &str, code: &str) -> Result<(), JsValue> {
+		let action = Action::from_name(action)
+			.ok_or_else(|| JsValue::from(format!("unknown action {}", action)))?;
+		let mut bindings = self.inner.borrow().bindings().clone();
+		bindings.bind(action, code);
+		self.inner.borrow_mut().set_bindings(bindings)
+	}
+
+	pub fn unbind_key(&self, code: &str) -> Result<(), JsValue> {
+		let mut bindings = self.inner.borrow().bindings().clone();
+		bindings.unbind(code);
+		self.inner.borrow_mut().set_bindings(bindings)
+	}
+
+	pub fn reset_bindings(&self) -> Result<(), JsValue> {
+		self.inner.borrow_mut().set_bindings(Bindings::default())
+	}
+
+	// the handling the player's got, defaults and all, as JSON like {"das_ms": 167, "arr_ms": 33,
+	// "soft_drop_factor": 20, "das_cut_ms": 0}
+	pub fn handling(&self) -> String {
+		self.inner.borrow().handling().to_json()
+	}
+
+	// changes whichever settings are given and leaves the rest. Like bindings, they're saved
+	// straight away. A replay only has room for the one set, so a game that's under way keeps
+	// what it started with and the change kicks in from the next one
+	pub fn set_handling(&self, handling: HandlingOptions) -> Result<(), JsValue> {
+		let changes = HandlingSettings::from_js(&handling)?;
+		let handling = self.inner.borrow().player_handling().merged(changes);
+		self.inner.borrow_mut().set_handling(handling)
+	}
+
+	// back to whatever the page asked for
+	pub fn reset_handling(&self) -> Result<(), JsValue> {
+		self.inner
+			.borrow_mut()
+			.set_handling(HandlingSettings::default())
+	}
+
+	// plays back the live game so far (or the one that just ended) from the start. The live game
+	// waits, paused, until stop_replay
+	pub fn watch_replay(&self) {
+		let replay = self.inner.borrow().replay().clone();
+		self.inner.borrow_mut().watch_replay(replay);
+	}
+
+	// the live game so far, as a code that can be shared in a link and loaded with
+	// load_replay_text
+	pub fn replay_text(&self) -> String {
+		self.inner.borrow().replay().to_text()
+	}
+
+	// saves the live game so far as a file, through the browser's usual download
+	pub fn download_replay(&self) -> Result<(), JsValue> {
+		let bytes = self.inner.borrow().replay().encode();
+		download("rusty_tetris.replay", &bytes)
+	}
+
+	// watches a replay file (or its text form) someone's given us. Anything that isn't one, or
+	// that was recorded with an incompatible version, fails with a message saying why
+	pub fn load_replay(&self, bytes: &[u8]) -> Result<(), JsValue> {
+		let replay = Replay::decode(bytes).map_err(|err| JsValue::from(err.to_string()))?;
+		self.inner.borrow_mut().watch_replay(replay);
+		Ok(())
+	}
+
+	pub fn load_replay_text(&self, text: &str) -> Result<(), JsValue> {
+		let replay = Replay::from_text(text).map_err(|err| JsValue::from(err.to_string()))?;
+		self.inner.borrow_mut().watch_replay(replay);
 		Ok(())
 	}
 
-	fn add_handlers(&self) -> Result<(), JsValue> {
-		{
-			let game_copy = self.inner.clone();
-			let closure = Closure::wrap(Box::new(move |event: web_sys::KeyboardEvent| {
-				game_copy
-					.borrow_mut()
-					.handle_key(event.key())
-					.expect("Something's gone wrong with handle key");
+	// every table, like {"marathon/guideline": [{"name": ..., "score": ...}, ...]}
+	pub fn high_scores(&self) -> String {
+		self.inner.borrow().high_scores().to_json()
+	}
+
+	// watches the game behind a high score, ranks counting from 0
+	pub fn watch_high_score(&self, table: &str, rank: usize) -> Result<(), JsValue> {
+		let replay = self
+			.inner
+			.borrow()
+			.high_scores()
+			.table(table)
+			.get(rank)
+			.and_then(|entry| entry.replay.clone())
+			.ok_or_else(|| JsValue::from("there's no replay for that score"))?;
+		self.load_replay_text(&replay)
+	}
+
+	pub fn player_name(&self) -> String {
+		scores::player_name()
+	}
+
+	// new high scores go under this name, which is remembered between visits
+	pub fn set_player_name(&self, name: &str) -> Result<(), JsValue> {
+		scores::set_player_name(name)
+	}
+
+	pub fn stop_replay(&self) {
+		self.inner.borrow_mut().stop_replay();
+	}
+
+	// 1 is normal speed, 0.5 half, 2 double and so on, up to 16
+	pub fn set_replay_speed(&self, speed: f64) {
+		self.inner.borrow_mut().set_replay_speed(speed);
+	}
+
+	pub fn toggle_replay_pause(&self) {
+		self.inner.borrow_mut().toggle_replay_pause();
+	}
+
+	// while a replay's paused, moves it along one tick (1/60th of a second) at a time
+	pub fn step_replay(&self) {
+		self.inner.borrow_mut().step_replay();
+	}
+}
+
+impl Game {
+	// keys come from the canvas while it has focus, or from anywhere on the page with global_keys
+	fn add_handlers(&self, global_keys: bool) -> Result<(), JsValue> {
+		let window = web_sys::window().unwrap();
+		let canvas: web_sys::EventTarget = self.inner.borrow().canvas.clone().into();
+		let (key_target, focus_target, focus_out, focus_in) = if global_keys {
+			let document: web_sys::EventTarget = window.document().unwrap().into();
+			(document, window.into(), "blur", "focus")
+		} else {
+			(canvas.clone(), canvas, "focusout", "focusin")
+		};
+
+		let game_copy = self.inner.clone();
+		let callbacks = self.callbacks.clone();
+		self.listen(&key_target, "keydown", move |event| {
+			let event = event.unchecked_into::<web_sys::KeyboardEvent>();
+			let code = event.code();
+			let is_bound = {
+				let mut game = game_copy.borrow_mut();
+				// we do our own auto repeat, so the browser's would only get in the way
+				if event.repeat() {
+					game.bindings().action(&code).is_some()
+				} else {
+					game.handle_key_down(&code)
+						.expect("Something's gone wrong with handle key down")
+				}
+			};
+			// leave everything else (tab, reload, devtools...) to the browser
+			if is_bound {
 				event.prevent_default();
-			}) as Box<dyn FnMut(_)>);
-			self.inner
+			}
+			// pausing and restarting happen right away rather than on the next tick
+			events::dispatch(&game_copy, &callbacks);
+		})?;
+
+		let game_copy = self.inner.clone();
+		self.listen(&key_target, "keyup", move |event| {
+			let event = event.unchecked_into::<web_sys::KeyboardEvent>();
+			let is_bound = game_copy
 				.borrow_mut()
-				.canvas
-				.add_event_listener_with_callback("keydown", closure.as_ref().unchecked_ref())?;
-			closure.forget();
-		}
+				.handle_key_up(&event.code())
+				.expect("Something's gone wrong with handle key up");
+			if is_bound {
+				event.prevent_default();
+			}
+		})?;
 
-		{
-			let game_copy = self.inner.clone();
-			let closure = Closure::wrap(Box::new(move |_: web_sys::Event| {
-				log::info!("lost focus");
-				game_copy
-					.borrow_mut()
-					.show_focus_banner()
-					.expect("Something's gone wrong with show focus");
-			}) as Box<dyn FnMut(_)>);
-			self.inner
+		let game_copy = self.inner.clone();
+		self.listen(&focus_target, focus_out, move |_| {
+			log::info!("lost focus");
+			game_copy
 				.borrow_mut()
-				.canvas
-				.add_event_listener_with_callback("focusout", closure.as_ref().unchecked_ref())?;
-			closure.forget();
-		}
+				.show_focus_banner()
+				.expect("Something's gone wrong with show focus");
+		})?;
 
-		{
-			let game_copy = self.inner.clone();
-			let closure = Closure::wrap(Box::new(move |_: web_sys::Event| {
-				game_copy
-					.borrow_mut()
-					.hide_focus_banner()
-					.expect("Something's gone wrong with hide focus");
-			}) as Box<dyn FnMut(_)>);
-			self.inner
+		let game_copy = self.inner.clone();
+		self.listen(&focus_target, focus_in, move |_| {
+			game_copy
 				.borrow_mut()
-				.canvas
-				.add_event_listener_with_callback("focusin", closure.as_ref().unchecked_ref())?;
-			closure.forget();
-		}
+				.hide_focus_banner()
+				.expect("Something's gone wrong with hide focus");
+		})?;
+
+		Ok(())
+	}
 
+	fn dispatch(&self) {
+		events::dispatch(&self.inner, &self.callbacks);
+	}
+
+	fn listen(
+		&self,
+		target: &web_sys::EventTarget,
+		kind: &'static str,
+		handler: impl FnMut(web_sys::Event) + 'static,
+	) -> Result<(), JsValue> {
+		let listener = Listener::new(target, kind, handler)?;
+		self.listeners.borrow_mut().push(listener);
 		Ok(())
 	}
+
+	// calls off the frame we're waiting on, and drops the loop so nothing asks for another
+	fn stop(&self) {
+		if let Some(frame_id) = self.frame_id.take() {
+			let _ = web_sys::window().unwrap().cancel_animation_frame(frame_id);
+		}
+		self.frame_loop.borrow_mut().take();
+	}
+}
+
+// a game that's just forgotten about by JS (or freed) tidies up after itself too
+impl Drop for Game {
+	fn drop(&mut self) {
+		self.destroy();
+	}
+}
+
+// an event listener that stops listening as soon as it's dropped
+struct Listener {
+	target: web_sys::EventTarget,
+	kind: &'static str,
+	closure: Closure<dyn FnMut(web_sys::Event)>,
+}
+
+impl Listener {
+	fn new(
+		target: &web_sys::EventTarget,
+		kind: &'static str,
+		handler: impl FnMut(web_sys::Event) + 'static,
+	) -> Result<Listener, JsValue> {
+		let closure = Closure::wrap(Box::new(handler) as Box<dyn FnMut(web_sys::Event)>);
+		target.add_event_listener_with_callback(kind, closure.as_ref().unchecked_ref())?;
+		Ok(Listener {
+			target: target.clone(),
+			kind,
+			closure,
+		})
+	}
+}
+
+impl Drop for Listener {
+	fn drop(&mut self) {
+		let _ = self
+			.target
+			.remove_event_listener_with_callback(self.kind, self.closure.as_ref().unchecked_ref());
+	}
 }
 
+// asks for the next frame, unless the loop's been stopped in the meantime
+fn schedule_frame(
+	frame_loop: &Weak<RefCell<Option<FrameCallback>>>,
+	frame_id: &Cell<Option<i32>>,
+) -> Result<(), JsValue> {
+	let frame_loop = match frame_loop.upgrade() {
+		Some(frame_loop) => frame_loop,
+		None => return Ok(()),
+	};
+	if let Some(callback) = frame_loop.borrow().as_ref() {
+		let id = web_sys::window()
+			.unwrap()
+			.request_animation_frame(callback.as_ref().unchecked_ref())?;
+		frame_id.set(Some(id));
+	}
+	Ok(())
+}
+
+// hands the bytes to the browser as a file, the same as clicking a download link would
+fn download(file_name: &str, bytes: &[u8]) -> Result<(), JsValue> {
+	let document = web_sys::window().unwrap().document().unwrap();
+	let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+	let options = web_sys::BlobPropertyBag::new();
+	options.set_type("application/octet-stream");
+	let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &options)?;
+	let url = web_sys::Url::create_object_url_with_blob(&blob)?;
+
+	let link = document
+		.create_element("a")?
+		.dyn_into::<web_sys::HtmlAnchorElement>()?;
+	link.set_href(&url);
+	link.set_download(file_name);
+	link.click();
+	web_sys::Url::revoke_object_url(&url)
+}
+
+// either the canvas itself, or the first one the selector finds
 pub fn create_canvas(
-	document: &Document,
-	width: f64,
-	height: f64,
+	target: &JsValue,
 ) -> Result<(web_sys::HtmlCanvasElement, Rc<CanvasRenderingContext2d>), JsValue> {
-	let canvas = document
-		.get_element_by_id("tetris")
-		.unwrap()
-		.dyn_into::<web_sys::HtmlCanvasElement>()?;
+	let canvas = match target.as_string() {
+		Some(selector) => web_sys::window()
+			.unwrap()
+			.document()
+			.unwrap()
+			.query_selector(&selector)?
+			.ok_or_else(|| JsValue::from(format!("nothing matches {}", selector)))?,
+		None => target.clone().dyn_into::<web_sys::Element>()?,
+	}
+	.dyn_into::<web_sys::HtmlCanvasElement>()
+	.map_err(|_| JsValue::from("the game can only be drawn on a canvas"))?;
 	canvas.style().set_property("background-color", "black")?;
 	canvas.style().set_property("margin-left", "auto")?;
 	canvas.style().set_property("margin-right", "auto")?;
 	canvas.style().set_property("display", "block")?;
 	canvas.set_attribute("tabindex", "0")?; // needed for keydown to work
-	canvas.set_width(width as u32);
-	canvas.set_height(height as u32);
 
 	let context = Rc::new(
 		canvas
@@ -0,0 +1,96 @@
+use super::options::from_js_object;
+use super::storage::{load_json, save_json};
+use rusty_tetris_engine::Handling;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen(typescript_custom_section)]
+const HANDLING_OPTIONS_TS: &str = r#"
+export interface HandlingOptions {
+	/** how long left or right has to be held before it starts repeating, in ms. Defaults to 167 */
+	das_ms?: number;
+	/** how long between repeats once it has, in ms. 0 slides straight to the wall. Defaults
+	 * to 33 */
+	arr_ms?: number;
+	/** how many times faster than gravity a held soft drop falls. Defaults to 20 */
+	soft_drop_factor?: number;
+	/** how long auto shift holds off after a rotation, in ms. Defaults to 0 */
+	das_cut_ms?: number;
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+	#[wasm_bindgen(typescript_type = "HandlingOptions")]
+	pub type HandlingOptions;
+}
+
+// the player's handling, in milliseconds so it's easy to read from JS and in storage. Anything
+// left out falls back to the engine's default
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HandlingSettings {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub das_ms: Option<u32>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub arr_ms: Option<u32>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub soft_drop_factor: Option<u32>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub das_cut_ms: Option<u32>,
+}
+
+impl HandlingSettings {
+	pub fn load(key: &str) -> HandlingSettings {
+		load_json(key).unwrap_or_default()
+	}
+
+	pub fn save(&self, key: &str) -> Result<(), JsValue> {
+		save_json(key, self)
+	}
+
+	// takes whatever plain object the page passed in
+	pub fn from_js(settings: &JsValue) -> Result<HandlingSettings, JsValue> {
+		from_js_object(settings, "handling")
+	}
+
+	// these settings, with anything the other has set taking priority
+	pub fn merged(self, other: HandlingSettings) -> HandlingSettings {
+		HandlingSettings {
+			das_ms: other.das_ms.or(self.das_ms),
+			arr_ms: other.arr_ms.or(self.arr_ms),
+			soft_drop_factor: other.soft_drop_factor.or(self.soft_drop_factor),
+			das_cut_ms: other.das_cut_ms.or(self.das_cut_ms),
+		}
+	}
+
+	pub fn handling(self) -> Handling {
+		let defaults = Handling::default();
+		let millis = |ms: Option<u32>, default: Duration| {
+			ms.map_or(default, |ms| Duration::from_millis(ms as u64))
+		};
+		Handling {
+			das: millis(self.das_ms, defaults.das),
+			arr: millis(self.arr_ms, defaults.arr),
+			soft_drop_factor: self
+				.soft_drop_factor
+				.unwrap_or(defaults.soft_drop_factor)
+				.max(1),
+			das_cut: millis(self.das_cut_ms, defaults.das_cut),
+		}
+	}
+
+	// every setting as it'll actually be played, defaults and all
+	pub fn to_json(self) -> String {
+		let handling = self.handling();
+		let millis = |duration: Duration| Some(duration.as_millis().min(u32::MAX as u128) as u32);
+		let settings = HandlingSettings {
+			das_ms: millis(handling.das),
+			arr_ms: millis(handling.arr),
+			soft_drop_factor: Some(handling.soft_drop_factor),
+			das_cut_ms: millis(handling.das_cut),
+		};
+		serde_json::to_string(&settings).unwrap_or_default()
+	}
+}
@@ -1,57 +1,44 @@
-use crate::game::game::{NUM_COLS, NUM_ROWS};
-use rand::{rngs::ThreadRng, Rng};
-use std::collections::BTreeSet;
-use std::convert::TryInto;
-use std::{collections::VecDeque, f64, rc::Rc};
+use super::handling::HandlingSettings;
+use super::input::{Action, Bindings};
+use super::save;
+use super::scores::{self, Entry, HighScores, Ranking};
+use super::storage::{rules_name, storage_key};
+use rusty_tetris_engine::{
+	ClearKind, Engine, Event, GameSummary, InputBuffer, LineClear, Piece, PieceKind, Playback,
+	Replay, Rules, SpinKind, Vector2D, MAX_PREVIEW_LEN, TICKS_PER_SECOND,
+};
+use std::{cell::RefCell, f64, rc::Rc, time::Duration};
 use wasm_bindgen::JsValue;
 use web_sys::CanvasRenderingContext2d;
 
-pub const FPS: i32 = (0.025 * 1000.0) as i32; // 0.025 sec -> 40 fps
-const MIN_SPEED: u32 = 5; // number of frames between updates
-const MAX_KEY_BUFF_LEN: usize = 3; // how many keys we'll keep track of before ignoring inputs
-const FRAMES_BEFORE_WE_SEAL_MOVE: u32 = 5;
-const FRAMES_BEFORE_WE_SEAL_MOVE_AFTER_SEND_TO_BOTTOM: u32 = FRAMES_BEFORE_WE_SEAL_MOVE - 2;
-const FRAMES_TO_SHOW_PURGATORY: u32 = 2;
-
-const COLOR_LINE: &str = "#46b5d1"; // how many keys we'll keep track of before ignoring inputs
-const COLOR_PYRAMID: &str = "#e43f5a"; // how many keys we'll keep track of before ignoring inputs
-const COLOR_SQUIGGLE: &str = "#b030b0"; // how many keys we'll keep track of before ignoring inputs
-const COLOR_REVERSE_SQUIGGLE: &str = "#72CB3B"; // how many keys we'll keep track of before ignoring inputs
-const COLOR_SQUARE: &str = "#c02739"; // how many keys we'll keep track of before ignoring inputs
-const COLOR_L: &str = "#FF971C"; // how many keys we'll keep track of before ignoring inputs
-const COLOR_REVERSE_L: &str = "#FF3213"; // how many keys we'll keep track of before ignoring inputs
+const TICK_MS: f64 = 1000. / TICKS_PER_SECOND as f64; // how much time each engine tick covers
+const MAX_FRAME_MS: f64 = 250.; // any longer between frames (say, a throttled tab) and we drop the backlog
+const PANEL_COLS: i32 = 6; // width of the stats panel to the right of the board, in blocks
+const PREVIEW_SCALE: f64 = 0.6; // how big upcoming pieces are, relative to the board
+const PREVIEW_ROWS: i32 = 2; // how much of the panel each upcoming piece takes up
+const NEXT_ROW: i32 = 3 + PREVIEW_ROWS; // where the upcoming pieces start, under the held one
+const STATS_ROW: i32 = NEXT_ROW + 2 + MAX_PREVIEW_LEN as i32 * PREVIEW_ROWS;
+const PANEL_ROWS: i32 = STATS_ROW + 10; // the canvas is always at least this tall, whatever the board
+const MAX_REPLAY_SPEED: f64 = 16.; // any faster and there's not much to see
+const LOCK_FADE: f64 = 0.6; // how much the current piece fades out right before it locks
+const HIGH_SCORES_SHOWN: usize = 5; // how much of the table fits under the game over banner
+const CALLOUT_TICKS: u64 = TICKS_PER_SECOND as u64 * 3 / 2; // how long "T-SPIN DOUBLE" and friends stay up
+
+const COLOR_LINE: &str = "#46b5d1";
+const COLOR_PYRAMID: &str = "#e43f5a";
+const COLOR_SQUIGGLE: &str = "#b030b0";
+const COLOR_REVERSE_SQUIGGLE: &str = "#72CB3B";
+const COLOR_SQUARE: &str = "#c02739";
+const COLOR_L: &str = "#FF971C";
+const COLOR_REVERSE_L: &str = "#FF3213";
 
 const COLOR_PURGATORY: &str = "#ffd700";
 const COLOR_BACKGROUND: &str = "#443737";
 const COLOR_BANNER: &str = "black";
 const COLOR_STROKE: &str = "black";
-
-#[derive(Debug, Clone, Copy, Default)]
-struct Vector2D {
-	x: i32,
-	y: i32,
-}
-
-#[derive(Debug, Default)]
-struct Square {
-	position: Vector2D,
-	color: String,
-	purgatory: bool,
-}
-
-#[derive(Debug, Clone, Copy)]
-struct FVector2D {
-	x: f64,
-	y: f64,
-}
-
-#[derive(Clone)]
-struct Piece {
-	top_left: Vector2D,
-	size: i32,
-	squares: Vec<Vector2D>, // square offsets from top_left
-	color: String,
-}
+const COLOR_TEXT: &str = "white";
+const COLOR_DISABLED: &str = "#777777";
+const COLOR_HIGHLIGHT: &str = "#ffd700";
 
 pub struct Inner {
 	pub canvas: web_sys::HtmlCanvasElement,
@@ -61,587 +48,499 @@ pub struct Inner {
 	height: f64,
 	rect_size: f64,
 
+	// keeps this game's saves and bindings apart from any others on the page
+	id: Option<String>,
+	// where the game in progress is kept, which depends on the rules as well as the id
+	save_key: String,
+
 	should_show_focus_banner: bool,
 	is_paused: bool,
-	is_game_over: bool,
-	did_win: bool,
-	score: u32,
-	key_buff: VecDeque<String>,
-
-	current_piece: Option<Piece>,
-	swapped_piece: Option<Piece>,
-	board_pieces: Vec<Vec<Square>>,
-
-	frames_between_updates: u32,
-	frames_until_update: u32,
-
-	frames_since_last_successful_move: u32,
-	frames_to_wait: u32,
-
-	should_send_to_bottom: bool,
-	should_swap_piece: bool,
-
-	rotations_to_perform: i32,
-	x_to_move: i32,
-	y_to_move: i32,
-
-	rng: ThreadRng,
+	// picked up from last time, and waiting for the player to say whether they want to carry on
+	is_resuming: bool,
+	// what the player's done since the last tick
+	input: InputBuffer,
+	bindings: Bindings,
+	// what the page asked for, and what the player's changed since, which wins out
+	default_handling: HandlingSettings,
+	handling: HandlingSettings,
+
+	engine: Engine,
+	// what the engine's said since the page last heard from us
+	events: Rc<RefCell<Vec<Event>>>,
+	high_scores: HighScores,
+	// where the game that just ended placed in its table, if it made it
+	last_rank: Option<usize>,
+	// where the current piece was before the latest tick, so we can draw it partway there
+	previous_piece: Option<Piece>,
+
+	// when we're watching a replay, the live game waits underneath until we're done
+	playback: Option<Playback>,
+	is_playback_paused: bool,
+	// ticks per tick, so 2 is double speed
+	playback_speed: f64,
+	playback_progress: f64,
+
+	// when the last animation frame ran, and how much time since then hasn't been simulated yet
+	last_frame: Option<f64>,
+	accumulator: f64,
 }
 
 impl Inner {
 	pub fn new(
-		width: f64,
-		height: f64,
+		mut rules: Rules,
+		default_handling: HandlingSettings,
 		rect_size: f64,
+		seed: Option<u64>,
+		id: Option<String>,
 		canvas: web_sys::HtmlCanvasElement,
 		context: Rc<CanvasRenderingContext2d>,
 	) -> Inner {
-		let inner = Inner {
-			canvas: canvas,
-			context: context,
-
-			width: width,
-			height: height,
-			rect_size: rect_size,
+		// each kind of game gets its own save, so trying out another mode or board size doesn't
+		// cost the game in progress
+		let save_key = storage_key(id.as_deref(), &format!("save/{}", rules_name(&rules)));
+		// even then, a save only picks up where it left off if it's from the seed the page asked
+		// for. Otherwise it's thrown away, rather than quietly overriding the options
+		let saved = save::load(&save_key).filter(|saved| is_same_game(saved, &rules, seed));
+		if saved.is_none() {
+			save::clear(&save_key);
+		}
+		let bindings = Bindings::load(&storage_key(id.as_deref(), "bindings"));
+		let handling = HandlingSettings::load(&storage_key(id.as_deref(), "handling"));
+		// a resumed game carries on with the handling it started with
+		rules.handling = default_handling.merged(handling).handling();
+		let is_resuming = saved.is_some();
+		let mut engine =
+			saved.unwrap_or_else(|| Engine::new(rules, seed.unwrap_or_else(random_seed)));
+		let events = Rc::new(RefCell::new(Vec::new()));
+		let queue = events.clone();
+		engine.observe(move |event: &Event| queue.borrow_mut().push(*event));
+
+		let mut inner = Inner {
+			canvas,
+			context,
+
+			width: 0.,
+			height: 0.,
+			rect_size,
+
+			id,
+			save_key,
 
 			should_show_focus_banner: false,
 			is_paused: false,
-			is_game_over: false,
-			did_win: false,
-			score: 0,
-			key_buff: VecDeque::with_capacity(MAX_KEY_BUFF_LEN),
-
-			current_piece: None,
-			swapped_piece: None,
-			board_pieces: Vec::new(),
-
-			frames_between_updates: MIN_SPEED,
-			frames_until_update: 0,
-
-			frames_since_last_successful_move: 0,
-			frames_to_wait: 0,
-
-			should_send_to_bottom: false,
-			should_swap_piece: false,
-
-			rotations_to_perform: 0,
-			x_to_move: 0,
-			y_to_move: 0,
-
-			rng: rand::thread_rng(),
+			is_resuming,
+			input: InputBuffer::default(),
+			bindings,
+			default_handling,
+			handling,
+
+			engine,
+			events,
+			high_scores: HighScores::load(),
+			last_rank: None,
+			previous_piece: None,
+
+			playback: None,
+			is_playback_paused: false,
+			playback_speed: 1.,
+			playback_progress: 0.,
+
+			last_frame: None,
+			accumulator: 0.,
 		};
-
-		return inner;
+		// a resumed game waits for the player, and whoever's listening hears it's paused
+		if is_resuming {
+			inner.set_paused(true);
+		}
+		inner.resize();
+		inner
 	}
 
-	fn reset(&mut self) {
-		self.is_game_over = false;
-		self.did_win = false;
-		self.score = 0;
-		self.frames_between_updates = MIN_SPEED;
-		self.frames_until_update = MIN_SPEED;
-		self.current_piece = None;
-		self.board_pieces.clear();
-		self.frames_to_wait = 0;
+	// fits the canvas to whichever board we're showing, with the panel alongside
+	fn resize(&mut self) {
+		let board = self.displayed().board();
+		let cols = board.width() + PANEL_COLS;
+		let rows = board.visible_rows().max(PANEL_ROWS);
+		self.width = cols as f64 * self.rect_size;
+		self.height = rows as f64 * self.rect_size;
+		self.canvas.set_width(self.width as u32);
+		self.canvas.set_height(self.height as u32);
 	}
 
-	pub fn focus(&self) -> Result<(), JsValue> {
-		self.canvas.focus()
+	// a fresh game with the same rules
+	pub fn reset(&mut self) {
+		if self.is_paused {
+			self.engine.notify_paused(false);
+		}
+		self.engine.reset(random_seed());
+		self.engine.set_handling(self.handling().handling());
+		self.previous_piece = None;
+		self.input = InputBuffer::default();
+		self.is_paused = false;
+		self.is_resuming = false;
+		self.last_rank = None;
+		save::clear(&self.save_key);
 	}
 
-	pub fn show_focus_banner(&mut self) -> Result<(), JsValue> {
-		log::info!("Show focus banner");
-		self.should_show_focus_banner = true;
-		Ok(())
+	fn storage_key(&self, name: &str) -> String {
+		storage_key(self.id.as_deref(), name)
 	}
 
-	pub fn hide_focus_banner(&mut self) -> Result<(), JsValue> {
-		log::info!("Hide focus banner");
-		self.should_show_focus_banner = false;
-		Ok(())
+	pub fn high_scores(&self) -> &HighScores {
+		&self.high_scores
 	}
 
-	pub fn tick(&mut self) -> Result<(), JsValue> {
-		self.pre_process_keys();
-		if !self.effectively_paused() {
-			if self.frames_until_update == 0 {
-				self.process_key();
-				self.update()?;
-				self.frames_until_update = self.frames_between_updates;
+	// puts a game that's just ended on the board, if it's good enough
+	fn record_high_score(&mut self) {
+		let entry = match Entry::from_engine(&self.engine, &scores::player_name()) {
+			Some(entry) => entry,
+			None => return,
+		};
+		let table = HighScores::table_name(&self.engine);
+		// every game on the page shares the one set of tables, and any of them might have
+		// changed it since we last looked
+		self.high_scores = HighScores::load();
+		let ranking = Ranking::of(self.engine.rules().mode);
+		self.last_rank = self.high_scores.add(&table, entry, ranking);
+		if self.last_rank.is_some() {
+			if let Err(err) = self.high_scores.save() {
+				log::warn!("couldn't save high scores: {:?}", err);
 			}
-			self.frames_until_update -= 1;
 		}
-		self.draw().expect("Something's gone wrong with draw");
-		Ok(())
 	}
 
-	pub fn handle_key(&mut self, key: String) -> Result<(), JsValue> {
-		log::info!("Received {}", key);
-		if self.key_buff.len() < MAX_KEY_BUFF_LEN {
-			self.key_buff.push_back(key);
+	// puts the game away in case the page gets closed. Nothing to keep if it's over or hasn't
+	// started, and a save we haven't resumed yet is already there
+	fn save(&self) {
+		if self.is_resuming || self.engine.is_game_over() || self.engine.ticks() == 0 {
+			return;
+		}
+		if let Err(err) = save::save(&self.save_key, &self.engine) {
+			log::warn!("couldn't save the game: {:?}", err);
 		}
-		Ok(())
 	}
 
-	fn effectively_paused(&self) -> bool {
-		self.should_show_focus_banner || self.is_paused || self.is_game_over
+	pub fn is_paused(&self) -> bool {
+		self.is_paused
 	}
 
-	pub fn pre_process_keys(&mut self) {
-		let mut should_reset = false;
-		if let Some(key) = self.key_buff.front() {
-			match key.as_str() {
-				"r" => {
-					log::info!("resetting");
-					should_reset = true;
-					self.key_buff.pop_front();
-				}
-
-				"Enter" => {
-					if self.is_game_over {
-						should_reset = true;
-					} else {
-						self.is_paused = !self.is_paused;
-					}
-					self.key_buff.pop_front();
-				}
-				_ => {}
-			}
+	// games that are over stay over, pausing or not
+	pub fn set_paused(&mut self, is_paused: bool) {
+		if self.engine.is_game_over() {
+			return;
 		}
-
-		// some things we need to do after our immutable borrows up top
-		if should_reset {
-			self.reset();
+		if self.is_paused != is_paused {
+			self.engine.notify_paused(is_paused);
 		}
-
-		// eats up any keys that would otherwise clog the buffer.
-		// Also prevents pause-buffering
-		if self.effectively_paused() {
-			self.key_buff.clear();
+		self.is_paused = is_paused;
+		if is_paused {
+			self.save();
+		} else {
+			// carrying on is the answer to "resume?", while pausing again leaves it up
+			self.is_resuming = false;
 		}
 	}
 
-	pub fn process_key(&mut self) {
-		if let Some(key) = self.key_buff.pop_front() {
-			if self.effectively_paused() {
-				return;
-			}
+	// forgets when the last frame was, so time spent not running doesn't all land on the next one
+	pub fn restart_clock(&mut self) {
+		self.last_frame = None;
+		self.accumulator = 0.;
+	}
 
-			match key.as_str() {
-				// NOTE: y is flipped here since that's the default for rendering, and it's easier
-				// to flip it just here than anytime we draw
-				"ArrowUp" => self.rotations_to_perform += 1,
-				"ArrowDown" => self.y_to_move += 1,
+	// everything the engine's said since the last time we asked
+	pub fn take_events(&mut self) -> Vec<Event> {
+		std::mem::take(&mut *self.events.borrow_mut())
+	}
 
-				"ArrowRight" => self.x_to_move = 1,
-				"ArrowLeft" => self.x_to_move = -1,
+	pub fn engine(&self) -> &Engine {
+		&self.engine
+	}
 
-				// reverse head
-				" " => self.should_send_to_bottom = true,
-				"s" => self.should_swap_piece = true,
+	// the live game's replay. The page only ever plays with the built-in randomizers, so there's
+	// always one
+	pub fn replay(&self) -> &Replay {
+		self.engine
+			.replay()
+			.expect("Something's gone wrong with the replay")
+	}
 
-				_ => {}
-			}
+	// whichever game we're showing: the replay if we're watching one, otherwise the live game
+	fn displayed(&self) -> &Engine {
+		match &self.playback {
+			Some(playback) => playback.engine(),
+			None => &self.engine,
 		}
 	}
 
-	fn update(&mut self) -> Result<(), JsValue> {
-		if self.frames_to_wait > 0 {
-			self.frames_to_wait -= 1;
-			return Ok(());
-		}
+	// puts the live game on hold and starts playing the replay from the top
+	pub fn watch_replay(&mut self, replay: Replay) {
+		self.set_paused(true);
+		self.input = InputBuffer::default();
+		self.previous_piece = None;
+		self.playback = Some(Playback::new(replay));
+		self.is_playback_paused = false;
+		self.playback_progress = 0.;
+		self.resize();
+	}
 
-		if self.should_swap_piece {
-			self.should_swap_piece = false;
-			let previously_swapped_piece = self.swapped_piece.take();
-			self.swapped_piece = self.current_piece.take();
+	// back to the live game, which stays paused until the player's ready
+	pub fn stop_replay(&mut self) {
+		self.playback = None;
+		self.previous_piece = None;
+		self.resize();
+	}
 
-			if let Some(mut current_piece) = previously_swapped_piece {
-				current_piece.top_left.y = 0;
-				self.current_piece = Some(current_piece);
-			}
+	pub fn set_replay_speed(&mut self, speed: f64) {
+		self.playback_speed = speed.clamp(0., MAX_REPLAY_SPEED);
+	}
+
+	pub fn toggle_replay_pause(&mut self) {
+		self.is_playback_paused = !self.is_playback_paused;
+	}
+
+	// moves a paused replay along by a single tick
+	pub fn step_replay(&mut self) {
+		if !self.is_playback_paused {
+			return;
+		}
+		if let Some(playback) = &mut self.playback {
+			self.previous_piece = None;
+			playback.step();
 		}
+	}
 
-		match &self.current_piece {
-			None => {
-				self.current_piece = Some(self.get_random_piece());
-				self.frames_since_last_successful_move = 0;
-
-				// fix the grid
-				// let's loop through the relevant rows, backwards, removing any that are full up
-
-				for row_index in (0..self.board_pieces.len()).rev() {
-					if let Some(row) = self.board_pieces.get(row_index) {
-						if row.len() == NUM_COLS.try_into().unwrap() {
-							self.board_pieces.remove(row_index);
-
-							// shift all rows above down one
-							for row_index in row_index..self.board_pieces.len() {
-								log::info!("Row index: {}", &row_index);
-								if let Some(row) = self.board_pieces.get_mut(row_index) {
-									for cell in row.iter_mut() {
-										cell.position.y += 1;
-									}
-								}
-							}
-						}
-					}
-				}
-			}
+	pub fn bindings(&self) -> &Bindings {
+		&self.bindings
+	}
 
-			Some(current_piece) => {
-				if self.frames_since_last_successful_move > FRAMES_BEFORE_WE_SEAL_MOVE {
-					let mut rows_to_check: BTreeSet<usize> = BTreeSet::new();
+	// saved straight away, so they're still there next time
+	pub fn set_bindings(&mut self, bindings: Bindings) -> Result<(), JsValue> {
+		self.bindings = bindings;
+		self.input = InputBuffer::default();
+		self.bindings.save(&self.storage_key("bindings"))
+	}
 
-					// add to board
-					for square in current_piece.squares.iter() {
-						let x = current_piece.top_left.x + square.x;
-						let y = current_piece.top_left.y + square.y;
+	// the handling that'll actually be played with
+	pub fn handling(&self) -> HandlingSettings {
+		self.default_handling.merged(self.handling)
+	}
 
-						let ty = (NUM_ROWS - y).try_into().unwrap(); // TODO: refactor
-						rows_to_check.insert(ty);
+	// only the player's own settings are saved, so the page can still change its defaults. They
+	// take over from the next game, or straight away if this one hasn't started
+	pub fn set_handling(&mut self, handling: HandlingSettings) -> Result<(), JsValue> {
+		self.handling = handling;
+		self.engine.set_handling(self.handling().handling());
+		self.handling.save(&self.storage_key("handling"))
+	}
 
-						// make sure we have enough rows before we push to them
-						while self.board_pieces.len() <= ty {
-							self.board_pieces.push(vec![]);
-						}
+	pub fn player_handling(&self) -> HandlingSettings {
+		self.handling
+	}
 
-						self.board_pieces.get_mut(ty).unwrap().push(Square {
-							position: Vector2D { x: x, y: y },
-							color: current_piece.color.clone(),
-							..Default::default()
-						});
-					}
+	pub fn focus(&self) -> Result<(), JsValue> {
+		self.canvas.focus()
+	}
 
-					// let's loop through the relevant rows, backwards, removing any that are full up
-					let mut should_redraw = false;
-					for row_index in rows_to_check.iter().rev() {
-						let row = self.board_pieces.get_mut(*row_index).unwrap();
-						if row.len() == NUM_COLS.try_into().unwrap() {
-							for cell in row.iter_mut() {
-								cell.purgatory = true;
-								should_redraw = true;
-							}
-						}
-					}
+	pub fn show_focus_banner(&mut self) -> Result<(), JsValue> {
+		log::info!("Show focus banner");
+		self.should_show_focus_banner = true;
+		// we won't hear about any keys being let go while we're out of focus
+		self.input = InputBuffer::default();
+		self.save();
+		Ok(())
+	}
 
-					self.current_piece = None;
+	pub fn hide_focus_banner(&mut self) -> Result<(), JsValue> {
+		log::info!("Hide focus banner");
+		self.should_show_focus_banner = false;
+		Ok(())
+	}
 
-					if should_redraw {
-						self.frames_to_wait = FRAMES_TO_SHOW_PURGATORY;
-					}
-					return Ok(());
-				}
-			}
+	// called on every animation frame with the browser's timestamp. Runs however many fixed
+	// ticks have built up since the last one, then draws
+	pub fn frame(&mut self, now: f64) -> Result<(), JsValue> {
+		let elapsed = match self.last_frame {
+			Some(last_frame) => (now - last_frame).clamp(0., MAX_FRAME_MS),
+			None => 0.,
 		};
+		self.last_frame = Some(now);
 
-		if let Some(current_piece) = &mut self.current_piece {
-			let mut did_move = false;
-			// move down
-			{
-				let mut y_to_move = 1 + self.y_to_move;
-				self.y_to_move = 0;
-
-				let mut did_send_to_bottom = false;
-				if self.should_send_to_bottom {
-					y_to_move = NUM_ROWS;
-					self.should_send_to_bottom = false;
-					did_send_to_bottom = true;
-					self.frames_since_last_successful_move = FRAMES_BEFORE_WE_SEAL_MOVE;
-				}
-
-				while y_to_move > 0 {
-					y_to_move -= 1;
-					current_piece.top_left.y += 1;
-					if Inner::does_collide(&current_piece, &self.board_pieces) {
-						// undo last move
-						current_piece.top_left.y -= 1;
-						break;
-					} else {
-						if did_send_to_bottom {
-							// if sent to bottom, still give a few frames to move, but not as many
-							self.frames_since_last_successful_move =
-								FRAMES_BEFORE_WE_SEAL_MOVE_AFTER_SEND_TO_BOTTOM;
-						} else {
-							did_move = true;
-						}
-					}
-				}
-			}
+		self.accumulator += elapsed;
+		while self.accumulator >= TICK_MS {
+			self.accumulator -= TICK_MS;
+			self.tick();
+		}
 
-			// move left/right
-			{
-				let x_delta = if self.x_to_move > 0 { 1 } else { -1 };
-				while self.x_to_move != 0 {
-					self.x_to_move -= x_delta;
-					current_piece.top_left.x += x_delta;
-					if Inner::does_collide(&current_piece, &self.board_pieces) {
-						current_piece.top_left.x -= x_delta;
-						break;
-					} else {
-						did_move = true;
-					}
-				}
-			}
+		self.draw(self.accumulator / TICK_MS)
+	}
 
-			// rotate
-			{
-				let rotate_delta = if self.rotations_to_perform > 0 { 1 } else { -1 };
-				while self.rotations_to_perform != 0 {
-					self.rotations_to_perform -= rotate_delta;
-					let backup = current_piece.squares.clone();
-					if rotate_delta > 0 {
-						Inner::rotate_clockwise(current_piece);
-					} else {
-						Inner::rotate_counter_clockwise(current_piece);
-					}
-					if Inner::does_collide(&current_piece, &self.board_pieces) {
-						// rotating counter-clockwise seemed like a lot of work, so we're just copying memory instead
-						current_piece.squares = backup;
-					} else {
-						did_move = true;
-					}
-				}
+	fn tick(&mut self) {
+		if let Some(playback) = &mut self.playback {
+			if self.is_playback_paused {
+				return;
 			}
-
-			if did_move {
-				self.frames_since_last_successful_move = 0;
-			} else {
-				self.frames_since_last_successful_move += 1;
+			self.previous_piece = playback.engine().current_piece().cloned();
+			self.playback_progress += self.playback_speed;
+			while self.playback_progress >= 1. {
+				self.playback_progress -= 1.;
+				playback.step();
 			}
+			return;
 		}
 
-		Ok(())
-	}
-
-	fn rotate_counter_clockwise(current_piece: &mut Piece) {
-		for square in current_piece.squares.iter_mut() {
-			// flip about the y-axis
-			square.x = current_piece.size - 1 - square.x;
-
-			// translate about the origin
-			let temp = square.x;
-			square.x = square.y;
-			square.y = temp;
+		if self.effectively_paused() {
+			// presses don't carry over a pause
+			self.input.forget_presses();
+			self.previous_piece = None;
+			return;
 		}
-	}
 
-	fn rotate_clockwise(current_piece: &mut Piece) {
-		for square in current_piece.squares.iter_mut() {
-			// flip about the x-axis
-			square.y = current_piece.size - 1 - square.y;
-
-			// translate about the origin
-			let temp = square.x;
-			square.x = square.y;
-			square.y = temp;
+		self.previous_piece = self.engine.current_piece().cloned();
+		let input = self.input.take();
+		self.engine.tick(input);
+		if self.engine.is_game_over() {
+			save::clear(&self.save_key);
+			self.record_high_score();
 		}
 	}
 
-	fn get_interception_point(current_piece: &Piece, board: &Vec<Vec<Square>>) -> i32 {
-		let mut extra_y = 0;
-		let mut temp_piece = current_piece.clone(); // clone to get mutable version
-		loop {
-			temp_piece.top_left.y += 1;
-			extra_y += 1;
-			if Inner::does_collide(&temp_piece, &board) {
-				return extra_y - 1;
+	// takes a KeyboardEvent.code. Returns whether the key's bound to anything
+	pub fn handle_key_down(&mut self, code: &str) -> Result<bool, JsValue> {
+		log::info!("Received {}", code);
+		let action = match self.bindings.action(code) {
+			Some(action) => action,
+			None => return Ok(false),
+		};
+
+		// a replay plays itself, so the only thing to do is pause it or watch it again
+		if let Some(playback) = &mut self.playback {
+			match action {
+				Action::Pause => self.is_playback_paused = !self.is_playback_paused,
+				Action::Restart => {
+					playback.rewind();
+					self.previous_piece = None;
+				}
+				_ => {}
 			}
+			return Ok(true);
 		}
-	}
 
-	fn does_collide(current_piece: &Piece, board: &Vec<Vec<Square>>) -> bool {
-		for square in current_piece.squares.iter() {
-			let x = current_piece.top_left.x + square.x;
-			let y = current_piece.top_left.y + square.y;
-
-			if x < 0 || x >= NUM_COLS {
-				return true;
+		match action {
+			Action::Restart => {
+				log::info!("resetting");
+				self.reset();
 			}
-			if y >= NUM_ROWS {
-				return true;
+			Action::Pause => {
+				if self.engine.is_game_over() {
+					self.reset();
+				} else {
+					self.set_paused(!self.is_paused);
+				}
 			}
-
-			// TODO: make more efficient
-			for row in board.iter() {
-				for board_piece in row.iter() {
-					if x == board_piece.position.x && y == board_piece.position.y {
-						return true;
+			_ => {
+				// everything else waits for the next tick, which sees every press no matter how
+				// quickly they come in
+				if let Some(button) = action.button() {
+					if !self.effectively_paused() {
+						self.input.press(button);
 					}
 				}
 			}
 		}
+		Ok(true)
+	}
+
+	pub fn handle_key_up(&mut self, code: &str) -> Result<bool, JsValue> {
+		let action = match self.bindings.action(code) {
+			Some(action) => action,
+			None => return Ok(false),
+		};
+
+		if let Some(button) = action.button() {
+			self.input.release(button);
+		}
+		Ok(true)
+	}
+
+	fn effectively_paused(&self) -> bool {
+		self.should_show_focus_banner || self.is_paused || self.engine.is_game_over()
+	}
 
-		return false;
-	}
-
-	fn get_random_piece(&mut self) -> Piece {
-		match self.rng.gen_range(0, 7) {
-			0 => Piece {
-				color: COLOR_LINE.to_string(),
-				top_left: Vector2D {
-					x: NUM_COLS / 2 - 2,
-					y: 0,
-				},
-				size: 4,
-				squares: vec![
-					Vector2D { x: 0, y: 1 },
-					Vector2D { x: 1, y: 1 },
-					Vector2D { x: 2, y: 1 },
-					Vector2D { x: 3, y: 1 },
-				],
-			},
-
-			1 => Piece {
-				color: COLOR_PYRAMID.to_string(),
-				top_left: Vector2D {
-					x: NUM_COLS / 2 - 2,
-					y: 0,
-				},
-				size: 3,
-				squares: vec![
-					Vector2D { x: 1, y: 0 },
-					Vector2D { x: 0, y: 1 },
-					Vector2D { x: 1, y: 1 },
-					Vector2D { x: 2, y: 1 },
-				],
-			},
-
-			2 => Piece {
-				color: COLOR_SQUIGGLE.to_string(),
-				top_left: Vector2D {
-					x: NUM_COLS / 2 - 2,
-					y: 0,
-				},
-				size: 3,
-				squares: vec![
-					Vector2D { x: 1, y: 1 },
-					Vector2D { x: 2, y: 1 },
-					Vector2D { x: 0, y: 2 },
-					Vector2D { x: 1, y: 2 },
-				],
-			},
-
-			3 => Piece {
-				color: COLOR_REVERSE_SQUIGGLE.to_string(),
-				top_left: Vector2D {
-					x: NUM_COLS / 2 - 2,
-					y: 0,
-				},
-				size: 3,
-				squares: vec![
-					Vector2D { x: 1, y: 1 },
-					Vector2D { x: 0, y: 1 },
-					Vector2D { x: 2, y: 2 },
-					Vector2D { x: 1, y: 2 },
-				],
-			},
-
-			4 => Piece {
-				color: COLOR_SQUARE.to_string(),
-				top_left: Vector2D {
-					x: NUM_COLS / 2 - 1,
-					y: 0,
-				},
-				size: 2,
-				squares: vec![
-					Vector2D { x: 0, y: 0 },
-					Vector2D { x: 0, y: 1 },
-					Vector2D { x: 1, y: 0 },
-					Vector2D { x: 1, y: 1 },
-				],
-			},
-
-			5 => Piece {
-				color: COLOR_L.to_string(),
-				top_left: Vector2D {
-					x: NUM_COLS / 2 - 1,
-					y: 0,
-				},
-				size: 3,
-				squares: vec![
-					Vector2D { x: 1, y: 0 },
-					Vector2D { x: 1, y: 1 },
-					Vector2D { x: 1, y: 2 },
-					Vector2D { x: 0, y: 2 },
-				],
-			},
-
-			6 => Piece {
-				color: COLOR_REVERSE_L.to_string(),
-				top_left: Vector2D {
-					x: NUM_COLS / 2 - 1,
-					y: 0,
-				},
-				size: 3,
-				squares: vec![
-					Vector2D { x: 1, y: 0 },
-					Vector2D { x: 1, y: 1 },
-					Vector2D { x: 1, y: 2 },
-					Vector2D { x: 2, y: 2 },
-				],
-			},
-
-			_ => panic!("Oopsie doodles"),
-		}
-	}
-
-	pub fn draw(&mut self) -> Result<(), JsValue> {
+	// alpha is how far we are between the last tick and the next one, from 0 to 1
+	pub fn draw(&mut self, alpha: f64) -> Result<(), JsValue> {
 		let context = &self.context;
 		context.clear_rect(0., 0., self.width, self.height);
 
+		let board = self.displayed().board();
+
 		// draw background
 		self.start_context(COLOR_BACKGROUND, COLOR_STROKE, 1.0, 3.);
-		for x in 0..NUM_COLS {
-			for y in 0..NUM_ROWS {
-				self.draw_rect(&Vector2D { x: x, y: y });
+		for x in 0..board.width() {
+			for y in 0..board.visible_rows() {
+				self.draw_rect(&Vector2D { x, y });
 			}
 		}
 		self.end_context();
 
-		for row in self.board_pieces.iter() {
-			for piece in row.iter() {
-				let color = if piece.purgatory {
-					COLOR_PURGATORY
-				} else {
-					&piece.color
-				};
+		for y in board.hidden_rows()..board.height() {
+			// full rows are about to be cleared
+			let is_full = board.is_row_full(y);
+			for x in 0..board.width() {
+				if let Some(kind) = board.get(x, y) {
+					let color = if is_full {
+						COLOR_PURGATORY
+					} else {
+						piece_color(kind)
+					};
 
-				self.start_context(color, COLOR_STROKE, 1.0, 3.);
-				self.draw_rect(&piece.position);
-				self.end_context();
+					self.start_context(color, COLOR_STROKE, 1.0, 3.);
+					self.draw_cell(&Vector2D { x, y });
+					self.end_context();
+				}
 			}
 		}
 
-		if let Some(current_piece) = &self.current_piece {
+		if let Some(current_piece) = self.displayed().current_piece() {
 			// draw ghost first in case real piece steps in
-			let extra_y = Inner::get_interception_point(&current_piece, &self.board_pieces);
-
-			self.start_context(&current_piece.color, COLOR_STROKE, 0.2, 3.);
-			for piece in current_piece.squares.iter() {
-				let x = current_piece.top_left.x + piece.x;
-				let y = current_piece.top_left.y + piece.y + extra_y;
-				self.draw_rect(&Vector2D { x: x, y: y });
+			let extra_y = self.displayed().ghost_offset().unwrap_or(0);
+
+			self.start_context(piece_color(current_piece.kind()), COLOR_STROKE, 0.2, 3.);
+			for position in current_piece.cells() {
+				self.draw_cell(&Vector2D {
+					x: position.x,
+					y: position.y + extra_y,
+				});
 			}
 			self.end_context();
 
-			self.start_context(&current_piece.color, COLOR_STROKE, 1.0, 3.);
-			for piece in current_piece.squares.iter() {
-				let x = current_piece.top_left.x + piece.x;
-				let y = current_piece.top_left.y + piece.y;
-				self.draw_rect(&Vector2D { x: x, y: y });
+			// fade the piece out as it gets closer to locking
+			let opacity = 1.0 - LOCK_FADE * self.displayed().lock_progress().min(1.0);
+			let offset = self.fall_offset(current_piece, alpha);
+			self.start_context(piece_color(current_piece.kind()), COLOR_STROKE, opacity, 3.);
+			for position in current_piece.cells() {
+				self.draw_cell_at(position.x as f64, position.y as f64 - offset);
 			}
 			self.end_context();
 		}
 
-		if self.is_paused {
-			self.draw_banner("PAUSED");
-		} else if self.is_game_over {
-			if self.did_win {
-				self.draw_banner("YOU WON!!!");
-			} else {
-				self.draw_banner("GAME OVER");
+		self.draw_panel();
+		self.draw_callout();
+
+		if let Some(playback) = &self.playback {
+			if self.is_playback_paused {
+				self.draw_banner("PAUSED");
+			} else if playback.engine().is_game_over() {
+				self.draw_game_over();
+			} else if playback.is_finished() {
+				self.draw_banner("END OF REPLAY");
 			}
+		} else if self.is_resuming {
+			self.draw_banner("RESUME?");
+			self.draw_subtitle("Pause to carry on, restart for a new game");
+		} else if self.is_paused {
+			self.draw_banner("PAUSED");
+		} else if self.engine.is_game_over() {
+			self.draw_game_over();
 		} else if self.should_show_focus_banner {
 			self.draw_banner("LOST FOCUS");
 		}
@@ -649,95 +548,411 @@ impl Inner {
 		Ok(())
 	}
 
+	fn draw_game_over(&self) {
+		if self.displayed().did_win() {
+			self.draw_banner("YOU WON!!!");
+		} else {
+			self.draw_banner("GAME OVER");
+		}
+		if let Some(summary) = self.displayed().summary() {
+			self.draw_summary(summary);
+		}
+		if self.playback.is_none() {
+			self.draw_high_scores();
+		}
+	}
+
+	// the top of the table the game that just ended went into, with its entry picked out
+	fn draw_high_scores(&self) {
+		let table = HighScores::table_name(&self.engine);
+		let entries = self.high_scores.table(&table);
+
+		let context = &self.context;
+		context.save();
+		context.set_font("14px Arial");
+		context.set_text_baseline("middle");
+		for (rank, entry) in entries.iter().take(HIGH_SCORES_SHOWN).enumerate() {
+			let color = if self.last_rank == Some(rank) {
+				COLOR_HIGHLIGHT
+			} else {
+				COLOR_TEXT
+			};
+			let y = self.board_height() / 2. + 80. + rank as f64 * 20.;
+			let margin = self.rect_size / 2.;
+			context.set_fill_style_str(color);
+			context.set_text_align("left");
+			context
+				.fill_text_with_max_width(
+					&format!("{}. {}", rank + 1, entry.name),
+					margin,
+					y,
+					self.board_width() / 2.,
+				)
+				.expect("Something's gone wrong here");
+			// sprints are ranked on time, so that's what's worth showing
+			let result = match Ranking::of(self.engine.rules().mode) {
+				Ranking::Score => entry.score.to_string(),
+				Ranking::Time => format_time(Duration::from_millis(entry.duration_ms)),
+			};
+			context.set_text_align("right");
+			context
+				.fill_text(&result, self.board_width() - margin, y)
+				.expect("Something's gone wrong here");
+		}
+		context.restore();
+	}
+
 	fn start_context(&self, fill_color: &str, stroke_color: &str, opacity: f64, line_width: f64) {
 		let context = &self.context;
 		context.save();
-		context.set_fill_style(&JsValue::from(fill_color));
-		context.set_stroke_style(&JsValue::from(stroke_color));
+		context.set_fill_style_str(fill_color);
+		context.set_stroke_style_str(stroke_color);
 		context.set_global_alpha(opacity);
 		context.set_line_width(line_width);
 	}
 
 	fn draw_rect(&self, rect: &Vector2D) {
-		&self.context.begin_path();
-		&self.context.rect(
+		self.draw_rect_at(
 			self.rect_size * rect.x as f64,
 			self.rect_size * rect.y as f64,
 			self.rect_size,
-			self.rect_size,
 		);
-		&self.context.fill();
-		&self.context.stroke();
+	}
+
+	// in pixels rather than board coordinates
+	fn draw_rect_at(&self, x: f64, y: f64, size: f64) {
+		self.context.begin_path();
+		self.context.rect(x, y, size, size);
+		self.context.fill();
+		self.context.stroke();
+	}
+
+	// like draw_rect, but in board coordinates, so anything up in the hidden rows is skipped
+	fn draw_cell(&self, position: &Vector2D) {
+		self.draw_cell_at(position.x as f64, position.y as f64);
+	}
+
+	// same again, but y can be partway between rows
+	fn draw_cell_at(&self, x: f64, y: f64) {
+		let y = y - self.displayed().board().hidden_rows() as f64;
+		if y < 0. {
+			return;
+		}
+		self.draw_rect_at(self.rect_size * x, self.rect_size * y, self.rect_size);
+	}
+
+	// how many rows short of its real position to draw the current piece, so falling looks
+	// smooth between ticks. Only for plain falls; anything else just snaps into place
+	fn fall_offset(&self, current_piece: &Piece, alpha: f64) -> f64 {
+		let previous_piece = match &self.previous_piece {
+			Some(previous_piece) => previous_piece,
+			None => return 0.,
+		};
+		if previous_piece.kind() != current_piece.kind()
+			|| previous_piece.rotation() != current_piece.rotation()
+		{
+			return 0.;
+		}
+
+		let (previous, current) =
+			match (previous_piece.cells().next(), current_piece.cells().next()) {
+				(Some(previous), Some(current)) => (previous, current),
+				_ => return 0.,
+			};
+		let fallen = current.y - previous.y;
+		if previous.x != current.x || fallen <= 0 {
+			return 0.;
+		}
+		fallen as f64 * (1. - alpha)
 	}
 
 	fn end_context(&self) {
-		&self.context.restore();
+		self.context.restore();
 	}
 
 	fn draw_banner(&self, text: &str) {
 		let context = &self.context;
 		context.save();
-		context.set_fill_style(&JsValue::from(COLOR_BANNER));
+		context.set_fill_style_str(COLOR_BANNER);
 		context.set_global_alpha(0.8);
-		let quarter_height = self.height / 4.;
+		let quarter_height = self.board_height() / 4.;
 		context.fill_rect(
 			0.,
 			quarter_height,
-			self.width,
-			self.height - quarter_height * 2.,
+			self.board_width(),
+			self.board_height() - quarter_height * 2.,
 		);
 		context.restore();
 
 		context.save();
 		context.begin_path();
 		context.set_font("60px Arial");
-		context.set_stroke_style(&JsValue::from("white"));
+		context.set_stroke_style_str("white");
 		context.set_font("60px Arial");
 		context.set_text_align("center");
 		context.set_text_baseline("middle");
-		context.set_fill_style(&JsValue::from("white"));
+		context.set_fill_style_str("white");
+		context
+			.fill_text_with_max_width(
+				text,
+				self.board_width() / 2.,
+				self.board_height() / 2.,
+				self.board_width(),
+			)
+			.expect("Something's gone wrong here");
+		context.restore();
+	}
+
+	fn draw_summary(&self, summary: &GameSummary) {
+		let text = if self.displayed().rules().mode.line_goal().is_some() {
+			// to the millisecond, since that's what a sprint comes down to
+			format!(
+				"Lines: {}   Time: {}",
+				summary.lines,
+				format_time(summary.duration())
+			)
+		} else {
+			let seconds = summary.duration().as_secs();
+			format!(
+				"Score: {}   Lines: {}   Time: {}:{:02}",
+				summary.score,
+				summary.lines,
+				seconds / 60,
+				seconds % 60
+			)
+		};
+		self.draw_subtitle(&text);
+	}
+
+	// a line of smaller text under the banner
+	fn draw_subtitle(&self, text: &str) {
+		let context = &self.context;
+		context.save();
+		context.set_font("18px Arial");
+		context.set_text_align("center");
+		context.set_text_baseline("middle");
+		context.set_fill_style_str("white");
+		context
+			.fill_text_with_max_width(
+				text,
+				self.board_width() / 2.,
+				self.board_height() / 2. + 50.,
+				self.board_width(),
+			)
+			.expect("Something's gone wrong here");
+		context.restore();
+	}
+
+	// shout out whatever the last lock was worth, fading away over a second or so
+	fn draw_callout(&self) {
+		let (clear, tick) = match self.displayed().last_clear() {
+			Some(last_clear) => last_clear,
+			None => return,
+		};
+		let age = self.displayed().ticks().saturating_sub(tick);
+		if age >= CALLOUT_TICKS {
+			return;
+		}
+
+		let context = &self.context;
+		context.save();
+		context.set_global_alpha(1.0 - age as f64 / CALLOUT_TICKS as f64);
+		context.set_font("bold 22px Arial");
+		context.set_text_align("center");
+		context.set_text_baseline("middle");
+		context.set_fill_style_str(COLOR_TEXT);
+		for (index, line) in callout_lines(&clear).iter().enumerate() {
+			context
+				.fill_text_with_max_width(
+					line,
+					self.board_width() / 2.,
+					self.rect_size * (3 + index) as f64,
+					self.board_width(),
+				)
+				.expect("Something's gone wrong here");
+		}
+		context.restore();
+	}
+
+	fn board_width(&self) -> f64 {
+		self.displayed().board().width() as f64 * self.rect_size
+	}
+
+	// just the visible part
+	fn board_height(&self) -> f64 {
+		self.displayed().board().visible_rows() as f64 * self.rect_size
+	}
+
+	fn draw_panel(&self) {
+		let engine = self.displayed();
+		self.draw_label("HOLD", 1);
+		if let Some(kind) = engine.held_piece() {
+			// greyed out until the current piece locks and we can hold again
+			let color = if engine.can_hold() {
+				piece_color(kind)
+			} else {
+				COLOR_DISABLED
+			};
+			self.draw_preview(kind, color, 2);
+		}
+
+		self.draw_label("NEXT", NEXT_ROW);
+		for (index, kind) in engine.queue().iter().enumerate() {
+			self.draw_preview(
+				*kind,
+				piece_color(*kind),
+				NEXT_ROW + 1 + index as i32 * PREVIEW_ROWS,
+			);
+		}
+
+		// a sprint's against the clock, so the timer takes the score's place
+		match engine.rules().mode.line_goal() {
+			Some(goal) => {
+				self.draw_stat("TIME", &format_time(engine.elapsed()), STATS_ROW);
+				self.draw_stat("LEVEL", &engine.level().to_string(), STATS_ROW + 3);
+				let lines = format!("{}/{}", engine.lines().min(goal), goal);
+				self.draw_stat("LINES", &lines, STATS_ROW + 6);
+			}
+			None => {
+				self.draw_stat("SCORE", &engine.score().to_string(), STATS_ROW);
+				self.draw_stat("LEVEL", &engine.level().to_string(), STATS_ROW + 3);
+				self.draw_stat("LINES", &engine.lines().to_string(), STATS_ROW + 6);
+			}
+		}
+
+		if self.playback.is_some() {
+			self.draw_label(&format!("REPLAY x{}", self.playback_speed), STATS_ROW + 9);
+		}
+	}
+
+	// a small version of the piece, centered in the panel at the given row
+	fn draw_preview(&self, kind: PieceKind, color: &str, row: i32) {
+		let piece = Piece::new(kind);
+		let size = self.rect_size * PREVIEW_SCALE;
+		let squares = piece.squares();
+		let min_x = squares.iter().map(|square| square.x).min().unwrap_or(0);
+		let max_x = squares.iter().map(|square| square.x).max().unwrap_or(0);
+		let min_y = squares.iter().map(|square| square.y).min().unwrap_or(0);
+
+		let panel_center = self.board_width() + (PANEL_COLS as f64 * self.rect_size) / 2.;
+		let left = panel_center - (max_x - min_x + 1) as f64 * size / 2.;
+		let top = row as f64 * self.rect_size;
+
+		self.start_context(color, COLOR_STROKE, 1.0, 2.);
+		for square in squares {
+			self.draw_rect_at(
+				left + (square.x - min_x) as f64 * size,
+				top + (square.y - min_y) as f64 * size,
+				size,
+			);
+		}
+		self.end_context();
+	}
+
+	fn draw_label(&self, label: &str, row: i32) {
+		let context = &self.context;
+		let x = self.board_width() + self.rect_size / 2.;
+		let max_width = self.width - x - self.rect_size / 2.;
+
+		context.save();
+		context.set_fill_style_str(COLOR_TEXT);
+		context.set_text_align("left");
+		context.set_text_baseline("top");
+		context.set_font("14px Arial");
+		context
+			.fill_text_with_max_width(label, x, row as f64 * self.rect_size, max_width)
+			.expect("Something's gone wrong here");
+		context.restore();
+	}
+
+	// a label with its value underneath, starting on the given row of the side panel
+	fn draw_stat(&self, label: &str, value: &str, row: i32) {
+		self.draw_label(label, row);
+
+		let context = &self.context;
+		let x = self.board_width() + self.rect_size / 2.;
+		let max_width = self.width - x - self.rect_size / 2.;
+
+		context.save();
+		context.set_fill_style_str(COLOR_TEXT);
+		context.set_text_align("left");
+		context.set_text_baseline("top");
+		context.set_font("bold 20px Arial");
 		context
-			.fill_text_with_max_width(text, self.width / 2., self.height / 2., self.width)
+			.fill_text_with_max_width(value, x, (row + 1) as f64 * self.rect_size, max_width)
 			.expect("Something's gone wrong here");
 		context.restore();
 	}
+}
+
+// whether a saved game was played under these rules, and from this seed if the page picked one
+fn is_same_game(saved: &Engine, rules: &Rules, seed: Option<u64>) -> bool {
+	let saved_rules = saved.rules();
+	saved_rules.rule_set == rules.rule_set
+		&& saved_rules.dimensions.clamped() == rules.dimensions.clamped()
+		&& saved_rules.mode == rules.mode
+		&& seed.is_none_or(|seed| saved.seed() == seed)
+}
+
+// the engine is fully deterministic, so any randomness has to come from out here
+fn random_seed() -> u64 {
+	let high = (js_sys::Math::random() * u32::MAX as f64) as u64;
+	let low = (js_sys::Math::random() * u32::MAX as f64) as u64;
+	(high << 32) | low
+}
+
+// like 1:23.456
+fn format_time(duration: Duration) -> String {
+	let millis = duration.as_millis();
+	format!(
+		"{}:{:02}.{:03}",
+		millis / 60_000,
+		millis / 1000 % 60,
+		millis % 1000
+	)
+}
+
+fn callout_lines(clear: &LineClear) -> Vec<String> {
+	let mut lines = Vec::new();
+	if clear.back_to_back {
+		lines.push("BACK-TO-BACK".to_string());
+	}
+
+	let spin = clear.spin.map(|spin| match spin.kind {
+		SpinKind::Mini => format!("{}-SPIN MINI", spin.piece),
+		SpinKind::Full => format!("{}-SPIN", spin.piece),
+	});
+	let kind = clear.kind.map(|kind| match kind {
+		ClearKind::Single => "SINGLE",
+		ClearKind::Double => "DOUBLE",
+		ClearKind::Triple => "TRIPLE",
+		ClearKind::Tetris => "TETRIS",
+	});
+	match (spin, kind) {
+		(Some(spin), Some(kind)) => lines.push(format!("{} {}", spin, kind)),
+		(Some(spin), None) => lines.push(spin),
+		// plain singles and doubles happen all the time, no need to shout about them
+		(None, Some(kind)) if clear.kind == Some(ClearKind::Tetris) => lines.push(kind.to_string()),
+		_ => {}
+	}
+
+	if clear.combo > 0 {
+		lines.push(format!("COMBO {}", clear.combo));
+	}
+	if clear.perfect_clear {
+		lines.push("PERFECT CLEAR".to_string());
+	}
+	lines
+}
 
-	// fn get_random_empty_space(&mut self) -> Option<Vector2D> {
-	// 	let empty_squares = self.get_empty_squares();
-	// 	if let Some(space) = empty_squares.choose(&mut self.rng) {
-	// 		return Some(Vector2D {
-	// 			x: space.x,
-	// 			y: space.y,
-	// 		});
-	// 	}
-	// 	return None;
-	// }
-
-	// fn get_empty_squares(&mut self) -> Vec<Vector2D> {
-	// 	let mut rv = vec![];
-	// 	for x in 0..self.num_squares_x {
-	// 		for y in 0..self.num_squares_y {
-	// 			if let ICellContents::Empty = self.contents_of_square(x, y) {
-	// 				rv.push(Vector2D { x: x, y: y });
-	// 			}
-	// 		}
-	// 	}
-	// 	return rv;
-	// }
-
-	// fn contents_of_square(&self, x: i32, y: i32) -> ICellContents {
-	// 	for pos in self.path.iter() {
-	// 		if pos.x == x && pos.y == y {
-	// 			return ICellContents::Snake;
-	// 		}
-	// 	}
-
-	// 	for pos in self.apples.iter() {
-	// 		if pos.x == x && pos.y == y {
-	// 			return ICellContents::Apple;
-	// 		}
-	// 	}
-
-	// 	return ICellContents::Empty;
-	// }
+fn piece_color(kind: PieceKind) -> &'static str {
+	match kind {
+		PieceKind::I => COLOR_LINE,
+		PieceKind::T => COLOR_PYRAMID,
+		PieceKind::S => COLOR_SQUIGGLE,
+		PieceKind::Z => COLOR_REVERSE_SQUIGGLE,
+		PieceKind::O => COLOR_SQUARE,
+		PieceKind::J => COLOR_L,
+		PieceKind::L => COLOR_REVERSE_L,
+	}
 }
@@ -0,0 +1,115 @@
+use super::storage::{load_json, save_json};
+use rusty_tetris_engine::Button;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use wasm_bindgen::JsValue;
+
+// everything a key can be bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+	MoveLeft,
+	MoveRight,
+	SoftDrop,
+	HardDrop,
+	RotateClockwise,
+	RotateCounterClockwise,
+	Rotate180,
+	Hold,
+	Pause,
+	Restart,
+}
+
+impl Action {
+	// the same names we save them under, like "rotate_clockwise"
+	pub fn from_name(name: &str) -> Option<Action> {
+		serde_json::from_value(serde_json::Value::String(name.to_string())).ok()
+	}
+
+	// the engine's side of things. Pausing and restarting are up to us
+	pub fn button(self) -> Option<Button> {
+		match self {
+			Action::MoveLeft => Some(Button::Left),
+			Action::MoveRight => Some(Button::Right),
+			Action::SoftDrop => Some(Button::SoftDrop),
+			Action::HardDrop => Some(Button::HardDrop),
+			Action::RotateClockwise => Some(Button::RotateClockwise),
+			Action::RotateCounterClockwise => Some(Button::RotateCounterClockwise),
+			Action::Rotate180 => Some(Button::Rotate180),
+			Action::Hold => Some(Button::Hold),
+			Action::Pause | Action::Restart => None,
+		}
+	}
+}
+
+// which keys do what. Keys are KeyboardEvent.code values like "KeyZ", which name the physical
+// key rather than whatever letter the layout puts on it, so AZERTY and Dvorak players get the
+// same spots as everyone else. Each action can have as many keys as you like, but each key only
+// does one thing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Bindings {
+	keys: BTreeMap<Action, Vec<String>>,
+}
+
+impl Default for Bindings {
+	fn default() -> Bindings {
+		let keys = |codes: &[&str]| codes.iter().map(|code| code.to_string()).collect();
+
+		let mut bindings = BTreeMap::new();
+		bindings.insert(Action::MoveLeft, keys(&["ArrowLeft", "Numpad4"]));
+		bindings.insert(Action::MoveRight, keys(&["ArrowRight", "Numpad6"]));
+		bindings.insert(Action::SoftDrop, keys(&["ArrowDown", "Numpad2"]));
+		bindings.insert(Action::HardDrop, keys(&["Space", "Numpad8"]));
+		bindings.insert(
+			Action::RotateClockwise,
+			keys(&["ArrowUp", "KeyX", "Numpad1", "Numpad5", "Numpad9"]),
+		);
+		bindings.insert(
+			Action::RotateCounterClockwise,
+			keys(&["KeyZ", "ControlLeft", "ControlRight", "Numpad3", "Numpad7"]),
+		);
+		bindings.insert(Action::Rotate180, keys(&["KeyA"]));
+		bindings.insert(
+			Action::Hold,
+			keys(&["KeyS", "KeyC", "ShiftLeft", "ShiftRight", "Numpad0"]),
+		);
+		bindings.insert(Action::Pause, keys(&["Enter", "Escape", "F1"]));
+		bindings.insert(Action::Restart, keys(&["KeyR"]));
+		Bindings { keys: bindings }
+	}
+}
+
+impl Bindings {
+	// whatever the player saved last time, or the defaults if they haven't (or we can't tell)
+	pub fn load(key: &str) -> Bindings {
+		load_json(key).unwrap_or_default()
+	}
+
+	pub fn save(&self, key: &str) -> Result<(), JsValue> {
+		save_json(key, self)
+	}
+
+	pub fn action(&self, code: &str) -> Option<Action> {
+		self.keys
+			.iter()
+			.find(|(_, codes)| codes.iter().any(|bound| bound == code))
+			.map(|(action, _)| *action)
+	}
+
+	// adds a key to an action, taking it away from whatever it did before
+	pub fn bind(&mut self, action: Action, code: &str) {
+		self.unbind(code);
+		self.keys.entry(action).or_default().push(code.to_string());
+	}
+
+	pub fn unbind(&mut self, code: &str) {
+		for codes in self.keys.values_mut() {
+			codes.retain(|bound| bound != code);
+		}
+	}
+
+	pub fn to_json(&self) -> String {
+		serde_json::to_string(self).unwrap_or_default()
+	}
+}
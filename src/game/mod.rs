@@ -1,2 +1,10 @@
+pub mod events;
+#[allow(clippy::module_inception)]
 pub mod game;
+pub mod handling;
 pub mod inner;
+pub mod input;
+pub mod options;
+pub mod save;
+pub mod scores;
+pub mod storage;
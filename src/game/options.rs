@@ -0,0 +1,123 @@
+use super::handling::HandlingSettings;
+use rusty_tetris_engine::{Dimensions, GameMode, RuleSet, Rules, DEFAULT_SPRINT_LINES};
+use serde::{de::DeserializeOwned, Deserialize};
+use wasm_bindgen::prelude::*;
+
+const DEFAULT_BLOCK_SIZE: f64 = 25.;
+
+#[wasm_bindgen(typescript_custom_section)]
+const GAME_OPTIONS_TS: &str = r#"
+export interface GameOptions {
+	/** how many columns wide the board is, from 4 to 16. Defaults to 10 */
+	cols?: number;
+	/** how many rows the player can see, from 4 to 50. Defaults to 20 */
+	rows?: number;
+	/** how many rows sit above the visible ones for pieces to spawn into, from 2 to 20.
+	 * Defaults to 4 */
+	hidden_rows?: number;
+	rule_set?: "guideline" | "nes" | "tgm";
+	/** marathon goes until topping out, while sprint is a race to clear sprint_lines lines.
+	 * Defaults to marathon */
+	mode?: "marathon" | "sprint";
+	/** how many lines a sprint takes. Defaults to 40 */
+	sprint_lines?: number;
+	/** how big each cell is drawn, in pixels. Defaults to 25 */
+	block_size?: number;
+	/** the first game's seed. Two games with the same seed and rules get the same pieces */
+	seed?: number;
+	/** how the keys feel. These are only the page's defaults: anything the player's changed with
+	 * set_handling takes priority */
+	handling?: HandlingOptions;
+	/** keeps this game's saved progress, key bindings and handling separate from other games on the page */
+	id?: string;
+	/** listen for keys anywhere on the page rather than just when the canvas has focus, so that
+	 * games with different bindings can be played side by side on one keyboard */
+	global_keys?: boolean;
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+	#[wasm_bindgen(typescript_type = "GameOptions")]
+	pub type GameOptions;
+}
+
+// what the page asked for when it made the game. Anything left out gets the usual default
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Options {
+	pub cols: Option<i32>,
+	pub rows: Option<i32>,
+	pub hidden_rows: Option<i32>,
+	pub rule_set: Option<String>,
+	pub mode: Option<String>,
+	pub sprint_lines: Option<u32>,
+	pub block_size: Option<f64>,
+	pub seed: Option<f64>,
+	pub id: Option<String>,
+	pub handling: HandlingSettings,
+	pub global_keys: bool,
+}
+
+impl Options {
+	// takes whatever plain object the page passed in, if it passed one in at all
+	pub fn from_js(options: Option<GameOptions>) -> Result<Options, JsValue> {
+		match options {
+			Some(options) => from_js_object(&options, "options"),
+			None => Ok(Options::default()),
+		}
+	}
+
+	pub fn rules(&self) -> Result<Rules, JsValue> {
+		let rule_set = match &self.rule_set {
+			Some(name) => RuleSet::from_name(name)
+				.ok_or_else(|| JsValue::from(format!("unknown rule set {}", name)))?,
+			None => RuleSet::default(),
+		};
+
+		let mode = match self.mode.as_deref() {
+			None | Some("marathon") => GameMode::Marathon,
+			Some("sprint") => GameMode::Sprint {
+				lines: self.sprint_lines.unwrap_or(DEFAULT_SPRINT_LINES).max(1),
+			},
+			Some(name) => return Err(JsValue::from(format!("unknown mode {}", name))),
+		};
+
+		let defaults = Dimensions::default();
+		let mut rules = Rules::new(rule_set);
+		rules.mode = mode;
+		rules.dimensions = Dimensions::new(
+			self.cols.unwrap_or(defaults.cols),
+			self.rows.unwrap_or(defaults.visible_rows),
+			self.hidden_rows.unwrap_or(defaults.hidden_rows),
+		);
+		Ok(rules)
+	}
+
+	// JS numbers only hold whole numbers exactly up to 2^53, which is plenty
+	pub fn seed(&self) -> Option<u64> {
+		self.seed
+			.filter(|seed| seed.is_finite() && *seed >= 0.)
+			.map(|seed| seed as u64)
+	}
+
+	pub fn block_size(&self) -> f64 {
+		self.block_size
+			.filter(|size| *size > 0.)
+			.unwrap_or(DEFAULT_BLOCK_SIZE)
+	}
+}
+
+// reads a plain object from the page into T, with undefined or null meaning all the defaults.
+// Going through JSON means serde_json does the work, since we have it anyway. `what` names the
+// object in the error if it doesn't fit
+pub fn from_js_object<T: DeserializeOwned + Default>(
+	value: &JsValue,
+	what: &str,
+) -> Result<T, JsValue> {
+	if value.is_undefined() || value.is_null() {
+		return Ok(T::default());
+	}
+	let json = String::from(js_sys::JSON::stringify(value)?);
+	serde_json::from_str(&json).map_err(|err| JsValue::from(format!("bad {}: {}", what, err)))
+}
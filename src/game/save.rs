@@ -0,0 +1,46 @@
+use super::storage::{load_json, local_storage, save_json};
+use rusty_tetris_engine::{Engine, SAVE_VERSION};
+use serde::Serialize;
+use serde_json::Value;
+use wasm_bindgen::JsValue;
+
+// the engine's exactly as it was, plus which version of it wrote this down
+#[derive(Serialize)]
+struct SavedGame<'a> {
+	version: u32,
+	engine: &'a Engine,
+}
+
+// keeps the game somewhere it'll survive the page being closed
+pub fn save(key: &str, engine: &Engine) -> Result<(), JsValue> {
+	save_json(
+		key,
+		&SavedGame {
+			version: SAVE_VERSION,
+			engine,
+		},
+	)
+}
+
+// the game saved last time, if there is one we can still read
+pub fn load(key: &str) -> Option<Engine> {
+	let mut saved: Value = load_json(key)?;
+	let version = saved.get("version")?.as_u64()?;
+	if version != SAVE_VERSION as u64 {
+		log::warn!("can't read a saved game from version {}", version);
+		return None;
+	}
+	match serde_json::from_value(saved.get_mut("engine")?.take()) {
+		Ok(engine) => Some(engine),
+		Err(err) => {
+			log::warn!("couldn't read the saved game: {}", err);
+			None
+		}
+	}
+}
+
+pub fn clear(key: &str) {
+	if let Some(storage) = local_storage() {
+		let _ = storage.remove_item(key);
+	}
+}
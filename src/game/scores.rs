@@ -0,0 +1,132 @@
+use super::storage::{load_json, local_storage, rules_name, save_json};
+use rusty_tetris_engine::{Engine, GameMode};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use wasm_bindgen::JsValue;
+
+const STORAGE_KEY: &str = "rusty_tetris.scores";
+const PLAYER_KEY: &str = "rusty_tetris.player";
+const DEFAULT_PLAYER: &str = "Player";
+pub const MAX_ENTRIES: usize = 10; // per table
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+	pub name: String,
+	pub score: u32,
+	pub lines: u32,
+	pub level: u32,
+	pub duration_ms: u64,
+	// when the game ended, as an ISO 8601 string
+	pub date: String,
+	// the whole game in its text form, for watching it back
+	#[serde(default)]
+	pub replay: Option<String>,
+}
+
+impl Entry {
+	// None if the game isn't over yet, or if it's a sprint that never made it to the end
+	pub fn from_engine(engine: &Engine, name: &str) -> Option<Entry> {
+		let summary = engine.summary()?;
+		if engine.rules().mode.line_goal().is_some() && !engine.did_win() {
+			return None;
+		}
+		Some(Entry {
+			name: name.to_string(),
+			score: summary.score,
+			lines: summary.lines,
+			level: engine.level(),
+			duration_ms: summary.duration().as_millis() as u64,
+			date: String::from(js_sys::Date::new_0().to_iso_string()),
+			replay: engine.replay().map(|replay| replay.to_text()),
+		})
+	}
+}
+
+// what makes one game better than another
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ranking {
+	// most points first
+	Score,
+	// quickest first
+	Time,
+}
+
+impl Ranking {
+	pub fn of(mode: GameMode) -> Ranking {
+		match mode {
+			GameMode::Marathon => Ranking::Score,
+			GameMode::Sprint { .. } => Ranking::Time,
+		}
+	}
+
+	fn is_better(self, entry: &Entry, other: &Entry) -> bool {
+		match self {
+			Ranking::Score => entry.score > other.score,
+			Ranking::Time => entry.duration_ms < other.duration_ms,
+		}
+	}
+}
+
+// the best few games for each mode and rule set, best first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct HighScores {
+	tables: BTreeMap<String, Vec<Entry>>,
+}
+
+impl HighScores {
+	pub fn load() -> HighScores {
+		load_json(STORAGE_KEY).unwrap_or_default()
+	}
+
+	pub fn save(&self) -> Result<(), JsValue> {
+		save_json(STORAGE_KEY, self)
+	}
+
+	// which table a game goes in. Only games played under the same rules on the same size board
+	// are ranked against each other
+	pub fn table_name(engine: &Engine) -> String {
+		rules_name(engine.rules())
+	}
+
+	pub fn table(&self, name: &str) -> &[Entry] {
+		self.tables
+			.get(name)
+			.map_or(&[], |entries| entries.as_slice())
+	}
+
+	// files the entry away, if it's good enough. Returns where it placed, counting from 0
+	pub fn add(&mut self, table: &str, entry: Entry, ranking: Ranking) -> Option<usize> {
+		let entries = self.tables.entry(table.to_string()).or_default();
+		// ties go to whoever got there first
+		let rank = entries
+			.iter()
+			.position(|other| ranking.is_better(&entry, other))
+			.unwrap_or(entries.len());
+		if rank >= MAX_ENTRIES {
+			return None;
+		}
+		entries.insert(rank, entry);
+		entries.truncate(MAX_ENTRIES);
+		Some(rank)
+	}
+
+	pub fn to_json(&self) -> String {
+		serde_json::to_string(self).unwrap_or_default()
+	}
+}
+
+// whoever's name the next high score goes under
+pub fn player_name() -> String {
+	local_storage()
+		.and_then(|storage| storage.get_item(PLAYER_KEY).ok().flatten())
+		.filter(|name| !name.trim().is_empty())
+		.unwrap_or_else(|| DEFAULT_PLAYER.to_string())
+}
+
+pub fn set_player_name(name: &str) -> Result<(), JsValue> {
+	match local_storage() {
+		Some(storage) => storage.set_item(PLAYER_KEY, name.trim()),
+		None => Err(JsValue::from("local storage isn't available")),
+	}
+}
@@ -0,0 +1,51 @@
+use rusty_tetris_engine::{Dimensions, GameMode, Rules};
+use serde::{de::DeserializeOwned, Serialize};
+use wasm_bindgen::JsValue;
+
+// where something's kept in local storage, like "rusty_tetris.bindings". Games with an id keep
+// their own, so that several on one page don't trip over each other
+pub fn storage_key(id: Option<&str>, name: &str) -> String {
+	match id {
+		Some(id) => format!("rusty_tetris.{}.{}", id, name),
+		None => format!("rusty_tetris.{}", name),
+	}
+}
+
+// which kind of game something belongs to, like "marathon/guideline", "sprint40/nes" or
+// "marathon/tgm/12x24". Games that can't be compared fairly get different names: sprints of
+// different lengths, or anything off the standard board
+pub fn rules_name(rules: &Rules) -> String {
+	let mut name = match rules.mode {
+		GameMode::Marathon => format!("marathon/{}", rules.rule_set.name()),
+		GameMode::Sprint { lines } => format!("sprint{}/{}", lines, rules.rule_set.name()),
+	};
+
+	let standard = Dimensions::default();
+	let dimensions = rules.dimensions.clamped();
+	if dimensions != standard {
+		name += &format!("/{}x{}", dimensions.cols, dimensions.visible_rows);
+		if dimensions.hidden_rows != standard.hidden_rows {
+			name += &format!("+{}", dimensions.hidden_rows);
+		}
+	}
+	name
+}
+
+// None when there isn't one, or the browser won't let us at it (private browsing, say)
+pub fn local_storage() -> Option<web_sys::Storage> {
+	web_sys::window()?.local_storage().ok().flatten()
+}
+
+// whatever's kept under the key, if there's anything there we can still read
+pub fn load_json<T: DeserializeOwned>(key: &str) -> Option<T> {
+	let json = local_storage()?.get_item(key).ok().flatten()?;
+	serde_json::from_str(&json).ok()
+}
+
+pub fn save_json<T: Serialize + ?Sized>(key: &str, value: &T) -> Result<(), JsValue> {
+	let json = serde_json::to_string(value).map_err(|err| JsValue::from(err.to_string()))?;
+	match local_storage() {
+		Some(storage) => storage.set_item(key, &json),
+		None => Err(JsValue::from("local storage isn't available")),
+	}
+}
@@ -1,15 +1,12 @@
 mod game;
-use game::game::Game;
 
 use wasm_bindgen::prelude::*;
 
+pub use game::game::Game;
+
+// runs as soon as the module's loaded. Games themselves are made from JS, with new Game(canvas)
 #[wasm_bindgen(start)]
-pub fn start() -> Result<(), JsValue> {
+pub fn start() {
 	wasm_logger::init(wasm_logger::Config::default());
-
 	log::info!("starting...");
-	let game = Game::create()?;
-	game.start()?;
-
-	Ok(())
 }